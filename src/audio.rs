@@ -0,0 +1,407 @@
+// Rust modules
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// External modules
+use sdl2::Sdl;
+use sdl2::AudioSubsystem;
+use sdl2::mixer::{self, Chunk, Channel, Music, Sdl2MixerContext, InitFlag};
+use serde_derive::{Deserialize};
+
+// Local modules
+use crate::settings::{GameSettings};
+
+const SFX_PATH: &str = "assets/sfx";
+// Optional file in the sfx folder that maps effects to other files,
+// e.g. { "MenuMove": "click.ogg" }. Effects not listed there use their default file.
+const SFX_MANIFEST: &str = "sfx.json";
+const MUSIC_PATH: &str = "assets/music";
+const MUSIC_EXTENSIONS: [&str; 3] = ["ogg", "mp3", "wav"];
+const FREQUENCY: i32 = 44_100;
+const CHUNK_SIZE: i32 = 1024;
+const SFX_CHANNELS: i32 = 16;
+// Milliseconds to fade out the old playlist and fade in the new one.
+// SDL mixer plays only one music track at a time, so the two can not overlap.
+const MUSIC_FADE_OUT: i32 = 500;
+const MUSIC_FADE_IN: i32 = 1000;
+// Volume range used in the settings
+const SETTINGS_MAX_VOLUME: i32 = 255;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    MenuMove,
+    MenuConfirm,
+    MenuBack,
+    ValueChange,
+    Error,
+    PlayerMove,
+    PiecePush,
+    Merge,
+    LevelSolved,
+}
+
+impl Sfx {
+    pub fn all() -> [Sfx; 9] {
+        [Sfx::MenuMove, Sfx::MenuConfirm, Sfx::MenuBack, Sfx::ValueChange, Sfx::Error,
+         Sfx::PlayerMove, Sfx::PiecePush, Sfx::Merge, Sfx::LevelSolved]
+    }
+
+    fn default_file(self) -> &'static str {
+        match self {
+            Sfx::MenuMove => "menu_move.wav",
+            Sfx::MenuConfirm => "menu_confirm.wav",
+            Sfx::MenuBack => "menu_back.wav",
+            Sfx::ValueChange => "value_change.wav",
+            Sfx::Error => "error.wav",
+            Sfx::PlayerMove => "player_move.wav",
+            Sfx::PiecePush => "piece_push.wav",
+            Sfx::Merge => "merge.wav",
+            Sfx::LevelSolved => "level_solved.wav",
+        }
+    }
+}
+
+// Music tracks a screen wants to hear, named after the files in the music folder.
+// The tracks are played one after another and the list starts over at the end,
+// a single track just loops.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Playlist {
+    tracks: Vec<String>,
+    shuffle: bool,
+}
+
+impl Playlist {
+    pub fn new(tracks: &[&str], shuffle: bool) -> Playlist {
+        Playlist {
+            tracks: tracks.iter().map(|track| track.to_string()).collect(),
+            shuffle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MusicState {
+    Stopped,
+    Playing,
+    // Waiting for the old track to fade out before the next playlist starts
+    FadingOut,
+}
+
+// Sound effects and music on top of SDL mixer.
+// If there is no audio device or a file is missing the game just stays silent,
+// so all play / stop calls can be made unconditionally.
+pub struct Audio {
+    _audio_subsystem: Option<AudioSubsystem>,
+    _mixer_context: Option<Sdl2MixerContext>,
+    opened: bool,
+    sfx: HashMap<Sfx, Chunk>,
+    music: HashMap<String, Music<'static>>,
+    music_state: MusicState,
+    playlist: Option<Playlist>,
+    next_playlist: Option<Playlist>,
+    // Indices of the loaded tracks of the playlist in playing order
+    order: Vec<usize>,
+    position: usize,
+    now_playing: Option<String>,
+    now_playing_changed: bool,
+    random: u64,
+}
+
+impl Audio {
+    pub fn new(sdl_context: &Sdl) -> Audio {
+        let audio_subsystem = sdl_context.audio()
+            .map_err(|e| println!("Could not initialize audio: {}", e))
+            .ok();
+
+        // Only needed for compressed music, WAV always works
+        let mixer_context = mixer::init(InitFlag::OGG | InitFlag::MP3)
+            .map_err(|e| println!("Could not initialize music decoders: {}", e))
+            .ok();
+
+        let opened = audio_subsystem.is_some() &&
+            match mixer::open_audio(FREQUENCY, mixer::DEFAULT_FORMAT, mixer::DEFAULT_CHANNELS, CHUNK_SIZE) {
+                Ok(()) => {
+                    mixer::allocate_channels(SFX_CHANNELS);
+                    true
+                },
+                Err(e) => {
+                    println!("Could not open audio device: {}", e);
+                    false
+                }
+            };
+
+        Audio {
+            _audio_subsystem: audio_subsystem,
+            _mixer_context: mixer_context,
+            opened,
+            sfx: HashMap::new(),
+            music: HashMap::new(),
+            music_state: MusicState::Stopped,
+            playlist: None,
+            next_playlist: None,
+            order: Vec::new(),
+            position: 0,
+            now_playing: None,
+            now_playing_changed: false,
+            random: random_seed(),
+        }
+    }
+
+    // Loads all sound effects and every music file in the music folder.
+    // A music track is named after its file name without extension.
+    pub fn load_resources(&mut self) {
+        if !self.opened {
+            return
+        }
+
+        let mut sfx_files = load_sfx_manifest();
+
+        for sfx in Sfx::all().iter() {
+            let file_name = sfx_files.remove(sfx).unwrap_or_else(|| sfx.default_file().to_string());
+            let path = Path::new(SFX_PATH).join(file_name);
+
+            match Chunk::from_file(&path) {
+                Ok(chunk) => {
+                    self.sfx.insert(*sfx, chunk);
+                },
+                Err(e) => {
+                    println!("Could not load sound effect '{}': {}", path.display(), e);
+                }
+            }
+        }
+
+        let entries = match fs::read_dir(MUSIC_PATH) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("Could not read music folder '{}': {}", MUSIC_PATH, e);
+                return
+            }
+        };
+
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let extension = path.extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| extension.to_lowercase());
+            let is_music = MUSIC_EXTENSIONS.iter().any(|known| extension.as_deref() == Some(*known));
+            let name = path.file_stem().and_then(|stem| stem.to_str()).map(|stem| stem.to_string());
+
+            if let (true, Some(name)) = (is_music, name) {
+                match Music::from_file(&path) {
+                    Ok(music) => {
+                        self.music.insert(name, music);
+                    },
+                    Err(e) => {
+                        println!("Could not load music '{}': {}", path.display(), e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Sets the volume of sound effects and music, the settings use 0..=255
+    pub fn apply_settings(&mut self, settings: &GameSettings) {
+        if !self.opened {
+            return
+        }
+
+        Channel::all().set_volume(to_mixer_volume(settings.get_sound_vol()));
+        Music::set_volume(to_mixer_volume(settings.get_music_vol()));
+    }
+
+    pub fn play_sfx(&self, sfx: Sfx) {
+        if let Some(chunk) = self.sfx.get(&sfx) {
+            // All channels busy is not worth a message, the effect is just skipped
+            let _ = Channel::all().play(chunk, 0);
+        }
+    }
+
+    // Switches to another playlist, the current track fades out first.
+    // Nothing happens if the playlist is already playing.
+    pub fn play_playlist(&mut self, playlist: &Playlist) {
+        let wanted = match self.music_state {
+            MusicState::FadingOut => self.next_playlist.as_ref(),
+            _ => self.playlist.as_ref(),
+        };
+
+        if !self.opened || wanted == Some(playlist) {
+            return
+        }
+
+        if self.music_state == MusicState::Playing && Music::is_playing() {
+            if let Err(e) = Music::fade_out(MUSIC_FADE_OUT) {
+                println!("Could not fade out music: {}", e);
+                Music::halt();
+            }
+        }
+
+        self.next_playlist = Some(playlist.clone());
+        self.music_state = MusicState::FadingOut;
+    }
+
+    pub fn stop_music(&mut self) {
+        if self.opened {
+            Music::halt();
+        }
+
+        self.music_state = MusicState::Stopped;
+        self.playlist = None;
+        self.next_playlist = None;
+        self.set_now_playing(None);
+    }
+
+    // Must be called once per frame to start the next track or playlist
+    pub fn update(&mut self) {
+        if !self.opened || Music::is_playing() {
+            return
+        }
+
+        match self.music_state {
+            MusicState::FadingOut => {
+                if let Some(playlist) = self.next_playlist.take() {
+                    self.start_playlist(playlist);
+                }
+            },
+            MusicState::Playing => {
+                self.next_track();
+            },
+            MusicState::Stopped => {
+            }
+        }
+    }
+
+    // Name of the new track if another one has started since the last call
+    pub fn take_now_playing(&mut self) -> Option<String> {
+        if self.now_playing_changed {
+            self.now_playing_changed = false;
+            self.now_playing.clone()
+        } else {
+            None
+        }
+    }
+
+    fn start_playlist(&mut self, playlist: Playlist) {
+        // Tracks that could not be loaded are skipped
+        self.order = (0..playlist.tracks.len())
+            .filter(|&index| self.music.contains_key(&playlist.tracks[index]))
+            .collect();
+        self.position = 0;
+
+        if playlist.shuffle {
+            self.shuffle_order();
+        }
+
+        self.playlist = Some(playlist);
+        self.start_track(MUSIC_FADE_IN);
+    }
+
+    fn next_track(&mut self) {
+        self.position += 1;
+
+        if self.position >= self.order.len() {
+            self.position = 0;
+
+            if self.playlist.as_ref().map(|playlist| playlist.shuffle) == Some(true) {
+                self.shuffle_order();
+            }
+        }
+
+        self.start_track(0);
+    }
+
+    fn start_track(&mut self, fade_ms: i32) {
+        let name = match (&self.playlist, self.order.get(self.position)) {
+            (Some(playlist), Some(&index)) => playlist.tracks[index].clone(),
+            _ => {
+                self.music_state = MusicState::Stopped;
+                self.set_now_playing(None);
+                return
+            }
+        };
+
+        // A single track loops forever, otherwise play each track once
+        let loops = if self.order.len() == 1 { -1 } else { 1 };
+        let music = &self.music[&name];
+        let result = if fade_ms > 0 {
+            music.fade_in(loops, fade_ms)
+        } else {
+            music.play(loops)
+        };
+
+        match result {
+            Ok(()) => {
+                self.music_state = MusicState::Playing;
+                self.set_now_playing(Some(name));
+            },
+            Err(e) => {
+                println!("Could not play music '{}': {}", name, e);
+                self.music_state = MusicState::Stopped;
+                self.set_now_playing(None);
+            }
+        }
+    }
+
+    fn set_now_playing(&mut self, name: Option<String>) {
+        if self.now_playing != name {
+            self.now_playing = name;
+            self.now_playing_changed = self.now_playing.is_some();
+        }
+    }
+
+    // Fisher-Yates shuffle, avoids playing the last track twice in a row
+    fn shuffle_order(&mut self) {
+        let last = self.order.last().cloned();
+
+        for i in (1..self.order.len()).rev() {
+            let j = (self.next_random() % (i as u64 + 1)) as usize;
+            self.order.swap(i, j);
+        }
+
+        if self.order.len() > 1 && self.order.first().cloned() == last {
+            let end = self.order.len() - 1;
+            self.order.swap(0, end);
+        }
+    }
+
+    // Xorshift, good enough to mix up a few tracks
+    fn next_random(&mut self) -> u64 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        self.random
+    }
+}
+
+// A missing manifest is fine, the default files are used then
+fn load_sfx_manifest() -> HashMap<Sfx, String> {
+    let path = Path::new(SFX_PATH).join(SFX_MANIFEST);
+
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(_) => {
+            return HashMap::new()
+        }
+    };
+
+    match serde_json::from_str(&data) {
+        Ok(sfx_files) => sfx_files,
+        Err(e) => {
+            println!("Could not read sound effect manifest '{}': {}", path.display(), e);
+            HashMap::new()
+        }
+    }
+}
+
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+
+    // Xorshift must not start with zero
+    nanos | 1
+}
+
+fn to_mixer_volume(volume: i16) -> i32 {
+    (volume as i32).clamp(0, SETTINGS_MAX_VOLUME) * mixer::MAX_VOLUME / SETTINGS_MAX_VOLUME
+}
@@ -2,16 +2,15 @@
 use std::rc::Rc;
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
 
 // Local modules
-use crate::game::{GameScreen};
+use crate::render::{Renderer};
 use crate::settings::{GameSettings};
 use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
 use crate::text_fx::{Font};
+use crate::input::{Action};
 
 pub struct AudioMenu {
     base: BaseMenu,
@@ -19,95 +18,127 @@ pub struct AudioMenu {
 
 impl AudioMenu {
     pub fn new() -> AudioMenu {
+        let mut base = BaseMenu::new(400, 100, 30, "AUDIO OPTIONS".to_string(), Vec::new(),
+            vec![
+                "SFX VOLUME:".to_string(),
+                "MUSIC VOLUME:".to_string(),
+                "BACK".to_string()
+            ]);
+        base.set_value_items(vec![0, 1]);
+
         AudioMenu {
-            base: BaseMenu::new(400, 100, 30, "AUDIO OPTIONS".to_string(), Vec::new(),
-                vec![
-                    "SFX VOLUME:".to_string(),
-                    "MUSIC VOLUME:".to_string(),
-                    "BACK".to_string()
-                ]),
+            base,
         }
     }
 
-    pub fn process(&mut self, event: &Event, game_screen: &mut GameScreen, settings: &mut GameSettings) {
-        match event {
-            Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
+    fn update_settings(&mut self, settings: &GameSettings) {
+        self.base.change_menu(0, format!("SFX VOLUME: {}", settings.get_sound_vol()));
+        self.base.change_menu(1, format!("MUSIC VOLUME: {}", settings.get_music_vol()));
+    }
+}
+
+impl Screen for AudioMenu {
+    fn enter(&mut self, context: &mut ScreenContext) {
+        self.update_settings(context.settings);
+    }
+
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let settings = &mut *context.settings;
+        let audio = &mut *context.audio;
+
+        let action = match self.base.process(event, action, audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        match action {
+            Action::Back => {
+                self.base.back(audio);
+                context.screen.back();
+            },
+            Action::Confirm => {
                 match self.base.get_selected() {
                     2 => {
-                        game_screen.main_menu();
+                        context.screen.back();
                     },
                     _ => {
                     }
                 }
             },
-            Event::KeyDown { keycode: Some(Keycode::Left), .. } => {
+            Action::Left => {
                 match self.base.get_selected() {
                     0 => {
                         let old_vol = settings.get_sound_vol();
                         settings.dec_sound_vol();
                         let new_vol = settings.get_sound_vol();
                         if old_vol != new_vol {
-                            // TODO: Change SDL sound volume
+                            audio.apply_settings(settings);
                             self.update_settings(settings);
                         }
+                        // Played at the new volume, so it can be heard
+                        self.base.value_changed(audio, old_vol != new_vol);
                     },
                     1 => {
                         let old_vol = settings.get_music_vol();
                         settings.dec_music_vol();
                         let new_vol = settings.get_music_vol();
                         if old_vol != new_vol {
-                            // TODO: Change SDL music volume
+                            audio.apply_settings(settings);
                             self.update_settings(settings);
                         }
+                        self.base.value_changed(audio, old_vol != new_vol);
                     },
                     _ => {
                     }
                 }
             },
-            Event::KeyDown { keycode: Some(Keycode::Right), .. } => {
+            Action::Right => {
                 match self.base.get_selected() {
                     0 => {
                         let old_vol = settings.get_sound_vol();
                         settings.inc_sound_vol();
                         let new_vol = settings.get_sound_vol();
                         if old_vol != new_vol {
-                            // TODO: Change SDL sound volume
+                            audio.apply_settings(settings);
                             self.update_settings(settings);
                         }
+                        // Played at the new volume, so it can be heard
+                        self.base.value_changed(audio, old_vol != new_vol);
                     },
                     1 => {
                         let old_vol = settings.get_music_vol();
                         settings.inc_music_vol();
                         let new_vol = settings.get_music_vol();
                         if old_vol != new_vol {
-                            // TODO: Change SDL music volume
+                            audio.apply_settings(settings);
                             self.update_settings(settings);
                         }
+                        self.base.value_changed(audio, old_vol != new_vol);
                     },
                     _ => {
                     }
                 }
             },
             _ => {
-                self.base.process(event);
             }
         }
     }
 
-    pub fn update(&mut self) {
+    fn update(&mut self, _context: &mut ScreenContext) {
         self.base.update()
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        self.base.draw(canvas)
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.base.draw(canvas, alpha)
     }
 
-    pub fn set_font(&mut self, font: &Rc<Font>) {
-        self.base.set_font(font);
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.base.get_selected())
     }
 
-    pub fn update_settings(&mut self, settings: &GameSettings) {
-        self.base.change_menu(0, format!("SFX VOLUME: {}", settings.get_sound_vol()));
-        self.base.change_menu(1, format!("MUSIC VOLUME: {}", settings.get_music_vol()));
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.base.set_font(font);
     }
 }
@@ -0,0 +1,159 @@
+// Rust modules
+
+// External modules
+
+// Local modules
+
+// The board only holds the state of a level, it does not know anything about
+// rendering. The rules are implemented in the simulation module.
+
+// (x, y) position on the board
+pub type Position = (u32, u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tile {
+    Floor,
+    Wall,
+    Goal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Polarity {
+    North,
+    South,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Piece {
+    // A metal block, attracted by every magnet
+    Block,
+    // A magnet, attracts opposite and repels same polarity
+    Magnet(Polarity),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [Direction::Left, Direction::Right, Direction::Up, Direction::Down]
+    }
+
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board {
+    width: u32,
+    height: u32,
+    tiles: Vec<Tile>,
+    pieces: Vec<Option<Piece>>,
+    player: Position,
+}
+
+impl Board {
+    pub fn new(width: u32, height: u32) -> Board {
+        let size = (width * height) as usize;
+
+        Board {
+            width,
+            height,
+            tiles: vec![Tile::Floor; size],
+            pieces: vec![None; size],
+            player: (0, 0),
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_tile(&self, pos: Position) -> Tile {
+        self.tiles[self.index(pos)]
+    }
+
+    pub fn set_tile(&mut self, pos: Position, tile: Tile) {
+        let index = self.index(pos);
+        self.tiles[index] = tile;
+    }
+
+    pub fn get_piece(&self, pos: Position) -> Option<Piece> {
+        self.pieces[self.index(pos)]
+    }
+
+    pub fn set_piece(&mut self, pos: Position, piece: Option<Piece>) {
+        let index = self.index(pos);
+        self.pieces[index] = piece;
+    }
+
+    pub fn get_player(&self) -> Position {
+        self.player
+    }
+
+    pub fn set_player(&mut self, pos: Position) {
+        self.player = pos;
+    }
+
+    pub fn is_solved(&self) -> bool {
+        let mut num_of_goals = 0;
+
+        for (tile, piece) in self.tiles.iter().zip(self.pieces.iter()) {
+            if *tile == Tile::Goal {
+                num_of_goals += 1;
+                if *piece != Some(Piece::Block) {
+                    return false
+                }
+            }
+        }
+
+        num_of_goals > 0
+    }
+
+    // True if nothing (wall, piece or player) occupies this position
+    pub fn is_free(&self, pos: Position) -> bool {
+        self.get_tile(pos) != Tile::Wall &&
+        self.get_piece(pos).is_none() &&
+        self.player != pos
+    }
+
+    pub fn neighbour(&self, pos: Position, direction: Direction) -> Option<Position> {
+        let (dx, dy) = direction.delta();
+        let nx = (pos.0 as i32) + dx;
+        let ny = (pos.1 as i32) + dy;
+
+        if nx < 0 || ny < 0 || nx >= (self.width as i32) || ny >= (self.height as i32) {
+            None
+        } else {
+            Some((nx as u32, ny as u32))
+        }
+    }
+
+    fn index(&self, pos: Position) -> usize {
+        (pos.1 * self.width + pos.0) as usize
+    }
+}
@@ -0,0 +1,199 @@
+// Rust modules
+
+// External modules
+use sdl2::rect::Rect;
+use sdl2::pixels::Color;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::board::{Board, Tile, Piece, Polarity, Position};
+use crate::simulation::{BoardEvent};
+
+// Number of frames a move animation takes
+const ANIMATION_FRAMES: u32 = 8;
+
+// Draws a board and animates the events of the last move.
+pub struct BoardView {
+    cell_size: u32,
+    // Pieces that are currently moving: (start, end)
+    moving: Vec<(Position, Position)>,
+    player_from: Option<Position>,
+    frame: u32,
+}
+
+impl BoardView {
+    pub fn new(cell_size: u32) -> BoardView {
+        BoardView {
+            cell_size,
+            moving: Vec::new(),
+            player_from: None,
+            frame: ANIMATION_FRAMES,
+        }
+    }
+
+    pub fn start_animation(&mut self, events: &[BoardEvent]) {
+        self.moving.clear();
+        self.player_from = None;
+
+        for event in events {
+            match *event {
+                BoardEvent::PlayerMoved(from, _) => {
+                    self.player_from = Some(from);
+                },
+                BoardEvent::PieceMoved(_, from, to) => {
+                    // A piece can move several times in a chain reaction,
+                    // only the start and the final position are animated.
+                    match self.moving.iter_mut().find(|(_, end)| *end == from) {
+                        Some(item) => {
+                            item.1 = to;
+                        },
+                        None => {
+                            self.moving.push((from, to));
+                        }
+                    }
+                },
+                BoardEvent::Merged(from, _) => {
+                    self.moving.retain(|(_, end)| *end != from);
+                },
+                _ => {
+                }
+            }
+        }
+
+        self.frame = 0;
+    }
+
+    pub fn stop_animation(&mut self) {
+        self.moving.clear();
+        self.player_from = None;
+        self.frame = ANIMATION_FRAMES;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.frame < ANIMATION_FRAMES
+    }
+
+    pub fn update(&mut self) {
+        if self.frame < ANIMATION_FRAMES {
+            self.frame += 1;
+        }
+    }
+
+    pub fn get_width(&self, board: &Board) -> u32 {
+        board.get_width() * self.cell_size
+    }
+
+    pub fn get_height(&self, board: &Board) -> u32 {
+        board.get_height() * self.cell_size
+    }
+
+    // Converts a pixel position into a board position, if it is inside the board
+    pub fn cell_at(&self, board: &Board, x: i32, y: i32, pixel_x: i32, pixel_y: i32) -> Option<Position> {
+        let cell = self.cell_size as i32;
+        let bx = (pixel_x - x).div_euclid(cell);
+        let by = (pixel_y - y).div_euclid(cell);
+
+        if bx < 0 || by < 0 || bx >= (board.get_width() as i32) || by >= (board.get_height() as i32) {
+            None
+        } else {
+            Some((bx as u32, by as u32))
+        }
+    }
+
+    // Alpha is the time since the last update in updates (0.0..1.0), it moves
+    // the pieces between the animation frames
+    pub fn draw(&self, canvas: &mut dyn Renderer, board: &Board, x: i32, y: i32, alpha: f64) {
+        self.draw_level(canvas, board, x, y, alpha);
+        self.draw_player(canvas, board.get_player(), x, y, alpha);
+    }
+
+    // Draws tiles and pieces but not the player
+    pub fn draw_level(&self, canvas: &mut dyn Renderer, board: &Board, x: i32, y: i32, alpha: f64) {
+        let t = self.progress(alpha);
+
+        for by in 0..board.get_height() {
+            for bx in 0..board.get_width() {
+                let pos = (bx, by);
+                let rect = self.cell_rect(x, y, pos, pos, 0, t);
+
+                let tile_color = match board.get_tile(pos) {
+                    Tile::Floor => Color::RGB(40, 40, 40),
+                    Tile::Wall => Color::RGB(120, 120, 120),
+                    Tile::Goal => Color::RGB(40, 120, 40),
+                };
+                canvas.set_draw_color(tile_color);
+                canvas.fill_rect(Some(rect)).unwrap();
+            }
+        }
+
+        for by in 0..board.get_height() {
+            for bx in 0..board.get_width() {
+                let pos = (bx, by);
+
+                if let Some(piece) = board.get_piece(pos) {
+                    let from = self.start_of(pos);
+                    let rect = self.cell_rect(x, y, from, pos, 4, t);
+                    canvas.set_draw_color(piece_color(piece));
+                    canvas.fill_rect(Some(rect)).unwrap();
+                }
+            }
+        }
+
+    }
+
+    pub fn draw_player(&self, canvas: &mut dyn Renderer, player: Position, x: i32, y: i32, alpha: f64) {
+        let from = if self.is_animating() { self.player_from.unwrap_or(player) } else { player };
+        let rect = self.cell_rect(x, y, from, player, 8, self.progress(alpha));
+        canvas.set_draw_color(Color::RGB(230, 200, 40));
+        canvas.fill_rect(Some(rect)).unwrap();
+    }
+
+    // Frame around a single cell, used as cursor
+    pub fn draw_cursor(&self, canvas: &mut dyn Renderer, pos: Position, x: i32, y: i32) {
+        let rect = self.cell_rect(x, y, pos, pos, 0, 1.0);
+        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        canvas.draw_rect(rect).unwrap();
+    }
+
+    fn start_of(&self, pos: Position) -> Position {
+        if self.is_animating() {
+            for (start, end) in self.moving.iter() {
+                if *end == pos {
+                    return *start
+                }
+            }
+        }
+
+        pos
+    }
+
+    // How far the animation has got, from 0.0 to 1.0
+    fn progress(&self, alpha: f64) -> f64 {
+        if self.is_animating() {
+            ((self.frame as f64 + alpha) / (ANIMATION_FRAMES as f64)).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    // Rectangle of a cell at t between two board positions and shrinked by border pixels
+    fn cell_rect(&self, x: i32, y: i32, from: Position, to: Position, border: u32, t: f64) -> Rect {
+        let cell = self.cell_size as f64;
+        let px = (from.0 as f64) + ((to.0 as f64) - (from.0 as f64)) * t;
+        let py = (from.1 as f64) + ((to.1 as f64) - (from.1 as f64)) * t;
+
+        Rect::new(
+            x + (px * cell) as i32 + (border as i32),
+            y + (py * cell) as i32 + (border as i32),
+            self.cell_size - 2 * border,
+            self.cell_size - 2 * border)
+    }
+}
+
+fn piece_color(piece: Piece) -> Color {
+    match piece {
+        Piece::Block => Color::RGB(180, 180, 200),
+        Piece::Magnet(Polarity::North) => Color::RGB(200, 40, 40),
+        Piece::Magnet(Polarity::South) => Color::RGB(40, 40, 200),
+    }
+}
@@ -0,0 +1,186 @@
+// Rust modules
+use std::convert::TryFrom;
+use std::path::Path;
+
+// External modules
+
+// Local modules
+use crate::level::{Level};
+use crate::level_pack::{LevelPack, LevelPackError};
+use crate::settings::{GameSettings};
+
+// Keeps track of the current level in a level pack.
+// A level is unlocked if it is the first one or the previous one has been solved.
+// The progress is stored in the game settings.
+pub struct Campaign {
+    pack: Option<LevelPack>,
+    current: usize,
+}
+
+impl Campaign {
+    pub fn new() -> Campaign {
+        Campaign {
+            pack: None,
+            current: 0,
+        }
+    }
+
+    pub fn load_pack<T: AsRef<Path>>(&mut self, path: T) -> Result<(), LevelPackError> {
+        self.pack = Some(LevelPack::load(path)?);
+        self.current = 0;
+
+        Ok(())
+    }
+
+    // Resume from the furthest unlocked level
+    pub fn resume(&mut self, settings: &GameSettings) -> Result<Level, LevelPackError> {
+        let pack = self.pack.as_ref().ok_or(LevelPackError::NoPack)?;
+
+        self.current = (settings.get_start_level() as usize).min(pack.len() - 1);
+        pack.load_level(self.current)
+    }
+
+    pub fn next(&mut self) -> Option<Result<Level, LevelPackError>> {
+        let pack = self.pack.as_ref()?;
+
+        if self.current + 1 < pack.len() {
+            self.current += 1;
+            Some(pack.load_level(self.current))
+        } else {
+            None
+        }
+    }
+
+    // Mark the current level as solved and unlock the next one
+    pub fn complete_level(&self, settings: &mut GameSettings) {
+        if let Some(pack) = &self.pack {
+            settings.set_level_solved(&pack.levels[self.current].file);
+
+            // The settings store the level as u8, larger packs stay at the last storable level
+            let next = u8::try_from((self.current + 1).min(pack.len() - 1)).unwrap_or(u8::MAX);
+            if next > settings.get_start_level() {
+                settings.set_start_level(next);
+            }
+        }
+    }
+
+    pub fn get_current(&self) -> usize {
+        self.current
+    }
+
+    // File name of the current level in the pack
+    pub fn get_file(&self) -> String {
+        match &self.pack {
+            Some(pack) => pack.levels[self.current].file.clone(),
+            None => String::new(),
+        }
+    }
+
+    pub fn get_par_moves(&self) -> u32 {
+        match &self.pack {
+            Some(pack) => pack.levels[self.current].par_moves,
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn campaign() -> Campaign {
+        let mut campaign = Campaign::new();
+        campaign.load_pack(crate::level_pack::PACK_PATH).unwrap();
+        campaign
+    }
+
+    #[test]
+    fn only_first_level_is_unlocked() {
+        let mut campaign = campaign();
+        let settings = GameSettings::new();
+
+        campaign.resume(&settings).unwrap();
+        assert_eq!(campaign.get_current(), 0);
+        assert!(!settings.is_level_solved(&campaign.get_file()));
+    }
+
+    #[test]
+    fn completing_unlocks_next_level() {
+        let mut campaign = campaign();
+        let mut settings = GameSettings::new();
+
+        campaign.resume(&settings).unwrap();
+        campaign.complete_level(&mut settings);
+        assert_eq!(settings.get_start_level(), 1);
+        assert!(settings.is_level_solved("level01.json"));
+
+        // Solving an earlier level again doesn't lock the later ones
+        campaign.next().unwrap().unwrap();
+        campaign.complete_level(&mut settings);
+        campaign.resume(&GameSettings::new()).unwrap();
+        campaign.complete_level(&mut settings);
+        assert_eq!(settings.get_start_level(), 2);
+    }
+
+    #[test]
+    fn last_level_stays_unlocked() {
+        let mut campaign = campaign();
+        let mut settings = GameSettings::new();
+
+        while let Some(level) = campaign.next() {
+            level.unwrap();
+        }
+        campaign.complete_level(&mut settings);
+        assert_eq!(settings.get_start_level() as usize, campaign.get_current());
+        assert!(campaign.next().is_none());
+    }
+
+    #[test]
+    fn resume_from_furthest_unlocked_level() {
+        let mut campaign = campaign();
+        let mut settings = GameSettings::new();
+
+        settings.set_start_level(2);
+        campaign.resume(&settings).unwrap();
+        assert_eq!(campaign.get_current(), 2);
+        assert_eq!(campaign.get_file(), "level03.json");
+
+        // Progress from a bigger pack is clamped to the last level
+        settings.set_start_level(200);
+        campaign.resume(&settings).unwrap();
+        assert_eq!(campaign.get_current(), 3);
+    }
+
+    #[test]
+    fn resume_without_pack() {
+        let mut campaign = Campaign::new();
+
+        match campaign.resume(&GameSettings::new()) {
+            Err(LevelPackError::NoPack) => {},
+            _ => panic!("expected NoPack"),
+        }
+        assert!(campaign.next().is_none());
+        assert_eq!(campaign.get_file(), "");
+    }
+
+    #[test]
+    fn progress_survives_settings_round_trip() {
+        let mut campaign = campaign();
+        let mut settings = GameSettings::new();
+
+        campaign.resume(&settings).unwrap();
+        campaign.complete_level(&mut settings);
+        campaign.next().unwrap().unwrap();
+        campaign.complete_level(&mut settings);
+
+        let data = serde_json::to_string(&settings).unwrap();
+        let loaded: GameSettings = serde_json::from_str(&data).unwrap();
+        assert_eq!(loaded.get_start_level(), 2);
+        assert!(loaded.is_level_solved("level01.json"));
+        assert!(loaded.is_level_solved("level02.txt"));
+        assert!(!loaded.is_level_solved("level03.json"));
+
+        campaign.resume(&loaded).unwrap();
+        assert_eq!(campaign.get_current(), 2);
+    }
+}
@@ -0,0 +1,176 @@
+// Rust modules
+use std::rc::Rc;
+
+// External modules
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::settings::{GameSettings};
+use crate::key_bindings::{BindError, bindable_actions, action_name, key_to_text};
+use crate::input::{Action, Device, controller_glyph};
+use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
+use crate::text_fx::{Font, StaticText};
+use crate::audio::{Audio, Sfx};
+
+pub struct ControlsMenu {
+    base: BaseMenu,
+    message: StaticText,
+    // Action that gets the next key pressed
+    waiting: Option<Action>,
+    // Controller buttons are shown instead of keys while a pad is used
+    device: Device,
+}
+
+impl ControlsMenu {
+    pub fn new() -> ControlsMenu {
+        let mut menu: Vec<String> = bindable_actions().iter()
+            .map(|action| format!("{}:", action_name(*action)))
+            .collect();
+        menu.push("RESET DEFAULTS".to_string());
+        menu.push("BACK".to_string());
+
+        ControlsMenu {
+            base: BaseMenu::new(400, 100, 30, "CONTROLS".to_string(), Vec::new(), menu),
+            message: StaticText::new(400, 500, String::new()),
+            waiting: None,
+            device: Device::Keyboard,
+        }
+    }
+
+    fn update_settings(&mut self, settings: &GameSettings) {
+        for (index, action) in bindable_actions().iter().enumerate() {
+            let binding = match self.device {
+                Device::Keyboard => key_to_text(settings.get_key_bindings().get(*action)),
+                Device::Controller => controller_glyph(*action).to_string(),
+            };
+            self.base.change_menu(index, format!("{}: {}", action_name(*action), binding));
+        }
+    }
+
+    fn set_device(&mut self, device: Device, settings: &GameSettings) {
+        self.device = device;
+        self.update_settings(settings);
+    }
+
+    // Waiting for the new key, Escape cancels
+    fn process_key(&mut self, event: &Event, action: Action, settings: &mut GameSettings, audio: &Audio) {
+        let key = match event {
+            Event::KeyDown { keycode: Some(key), repeat: false, .. } => *key,
+            _ => {
+                return
+            }
+        };
+
+        if key == Keycode::Escape {
+            self.cancel(audio);
+            return
+        }
+
+        match settings.get_key_bindings_mut().set(action, key) {
+            Ok(()) => {
+                self.waiting = None;
+                self.set_message(String::new());
+                self.update_settings(settings);
+                audio.play_sfx(Sfx::MenuConfirm);
+            },
+            Err(BindError::Reserved) => {
+                self.set_message(format!("{} IS RESERVED", key_to_text(key)));
+                audio.play_sfx(Sfx::Error);
+            },
+            Err(BindError::Conflict(other)) => {
+                self.set_message(format!("{} IS USED BY {}", key_to_text(key), action_name(other)));
+                audio.play_sfx(Sfx::Error);
+            }
+        }
+    }
+
+    fn cancel(&mut self, audio: &Audio) {
+        self.waiting = None;
+        self.set_message(String::new());
+        self.base.back(audio);
+    }
+
+    fn set_message(&mut self, text: String) {
+        self.message.set_text(text);
+        self.message.set_x(400);
+        self.message.center();
+    }
+}
+
+impl Screen for ControlsMenu {
+    fn enter(&mut self, context: &mut ScreenContext) {
+        self.set_device(context.device, context.settings);
+    }
+
+    // Needs the raw event to read the new key
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let settings = &mut *context.settings;
+        let audio = &*context.audio;
+
+        if let Some(waiting) = self.waiting {
+            if action == Some(Action::Back) && self.device == Device::Controller {
+                self.cancel(audio);
+            } else {
+                self.process_key(event, waiting, settings, audio);
+            }
+            return
+        }
+
+        let action = match self.base.process(event, action, audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        if action == Action::Back {
+            self.set_message(String::new());
+            self.base.back(audio);
+            context.screen.back();
+        } else if action == Action::Confirm {
+            let actions = bindable_actions();
+            let selected = self.base.get_selected();
+
+            if selected < actions.len() && self.device == Device::Controller {
+                self.set_message("CONTROLLER BUTTONS ARE FIXED".to_string());
+                audio.play_sfx(Sfx::Error);
+            } else if selected < actions.len() {
+                self.waiting = Some(actions[selected]);
+                self.set_message(format!("PRESS A KEY FOR {}", action_name(actions[selected])));
+            } else if selected == actions.len() {
+                settings.get_key_bindings_mut().reset();
+                self.update_settings(settings);
+                self.set_message("DEFAULT KEYS RESTORED".to_string());
+            } else {
+                self.set_message(String::new());
+                context.screen.back();
+            }
+        }
+    }
+
+    // Shows the buttons of the device the player uses right now
+    fn update(&mut self, context: &mut ScreenContext) {
+        if self.device != context.device {
+            self.set_device(context.device, context.settings);
+        }
+
+        self.base.update()
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.base.draw(canvas, alpha);
+        self.message.draw(canvas);
+    }
+
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.base.get_selected())
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.base.set_font(font);
+        self.message.set_font(font);
+    }
+}
@@ -2,15 +2,15 @@
 use std::rc::Rc;
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
 
 // Local modules
-use crate::game::{GameScreen};
+use crate::render::{Renderer};
 use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
 use crate::text_fx::{Font};
+use crate::audio::{Playlist};
+use crate::input::{Action};
 
 pub struct CreditMenu {
     base: BaseMenu,
@@ -29,27 +29,47 @@ impl CreditMenu {
             ], vec!["BACK".to_string()]),
         }
     }
+}
+
+impl Screen for CreditMenu {
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let action = match self.base.process(event, action, context.audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
 
-    pub fn process(&mut self, event: &Event, game_screen: &mut GameScreen) {
-        match event {
-            Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
-                game_screen.main_menu();
+        match action {
+            Action::Confirm => {
+                context.screen.back();
+            },
+            Action::Back => {
+                self.base.back(context.audio);
+                context.screen.back();
             },
             _ => {
-                self.base.process(event);
             }
         }
     }
 
-    pub fn update(&mut self) {
+    fn update(&mut self, _context: &mut ScreenContext) {
         self.base.update();
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        self.base.draw(canvas);
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.base.draw(canvas, alpha);
     }
 
-    pub fn set_font(&mut self, font: &Rc<Font>) {
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.base.get_selected())
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
         self.base.set_font(font);
     }
+
+    fn playlist(&self) -> Playlist {
+        Playlist::new(&["credits"], false)
+    }
 }
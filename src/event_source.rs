@@ -0,0 +1,76 @@
+// Rust modules
+
+// External modules
+use sdl2::EventPump;
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Scancode, Mod};
+use sdl2::mouse::{MouseButton, MouseState};
+
+// Where the game gets its events from, once per frame
+pub trait EventSource {
+    fn poll(&mut self) -> Vec<Event>;
+}
+
+// The events of the window, keyboard, mouse and controllers
+impl EventSource for EventPump {
+    fn poll(&mut self) -> Vec<Event> {
+        self.poll_iter().collect()
+    }
+}
+
+// Plays back events at given frames, e.g. from a script
+pub struct ScriptedEvents {
+    // Sorted by frame, events of the same frame keep their order
+    events: Vec<(u32, Event)>,
+    frame: u32,
+    next: usize,
+}
+
+impl ScriptedEvents {
+    pub fn new() -> ScriptedEvents {
+        ScriptedEvents {
+            events: Vec::new(),
+            frame: 0,
+            next: 0,
+        }
+    }
+
+    pub fn push(&mut self, frame: u32, event: Event) {
+        let index = self.events.iter().position(|(f, _)| *f > frame).unwrap_or(self.events.len());
+        self.events.insert(index, (frame, event));
+    }
+
+    // A key is pressed and released in the same frame
+    pub fn push_key(&mut self, frame: u32, keycode: Keycode) {
+        let scancode = Scancode::from_keycode(keycode);
+
+        self.push(frame, Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode,
+            keymod: Mod::NOMOD, repeat: false });
+        self.push(frame, Event::KeyUp { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode,
+            keymod: Mod::NOMOD, repeat: false });
+    }
+
+    // The position is in logical coordinates, the mouse moves there first
+    pub fn push_click(&mut self, frame: u32, x: i32, y: i32) {
+        self.push(frame, Event::MouseMotion { timestamp: 0, window_id: 0, which: 0,
+            mousestate: MouseState::from_sdl_state(0), x, y, xrel: 0, yrel: 0 });
+        self.push(frame, Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0,
+            mouse_btn: MouseButton::Left, clicks: 1, x, y });
+        self.push(frame, Event::MouseButtonUp { timestamp: 0, window_id: 0, which: 0,
+            mouse_btn: MouseButton::Left, clicks: 1, x, y });
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn poll(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        while self.next < self.events.len() && self.events[self.next].0 <= self.frame {
+            events.push(self.events[self.next].1.clone());
+            self.next += 1;
+        }
+
+        self.frame += 1;
+        events
+    }
+}
@@ -0,0 +1,333 @@
+// Rust modules
+use std::rc::Rc;
+use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// External modules
+use sdl2::render::{BlendMode};
+use sdl2::pixels::Color;
+use sdl2::rect::{Rect, Point};
+
+// Local modules
+use crate::render::{Renderer};
+use crate::text_fx::{StaticText, Font};
+use crate::video::{LOGICAL_WIDTH};
+
+// Number of frames the statistics are calculated from
+pub const WINDOW_SIZE: usize = 120;
+
+// Frame time graph: one bar per frame, the height is scaled from milliseconds
+const GRAPH_X: i32 = 10;
+const GRAPH_Y: i32 = 260;
+const GRAPH_BAR_WIDTH: u32 = 5;
+const GRAPH_PIXELS_PER_MS: f64 = 4.0;
+const GRAPH_MAX_HEIGHT: u32 = 100;
+// Frames above the target line are too slow for the frame rate limit,
+// without a limit they are compared with this frame rate
+const GRAPH_UNLIMITED_FPS: u32 = 60;
+
+// Number of canvas operations since the last frame, counted by the renderer
+static DRAW_CALLS: AtomicU32 = AtomicU32::new(0);
+
+pub fn count_draw_call() {
+    DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+// Returns the draw calls of the current frame and starts counting the next one
+pub fn take_draw_calls() -> u32 {
+    DRAW_CALLS.swap(0, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameSample {
+    // The whole frame including the time spent waiting
+    pub frame: Duration,
+    // All updates of the frame, there may be none or several
+    pub update: Duration,
+    pub draw: Duration,
+    pub draw_calls: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimingSummary {
+    pub min: Duration,
+    pub avg: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl TimingSummary {
+    fn to_text(self, label: &str) -> String {
+        format!("{:<6}{:>5.1}{:>5.1}{:>5.1}{:>5.1}{:>5.1}", label,
+            to_ms(self.min), to_ms(self.avg), to_ms(self.p95), to_ms(self.p99), to_ms(self.max))
+    }
+}
+
+// Timings of the last frames
+pub struct FrameStats {
+    samples: VecDeque<FrameSample>,
+}
+
+impl FrameStats {
+    pub fn new() -> FrameStats {
+        FrameStats {
+            samples: VecDeque::with_capacity(WINDOW_SIZE),
+        }
+    }
+
+    pub fn add(&mut self, sample: FrameSample) {
+        if self.samples.len() == WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    // Frames per second over the whole window, 0 until the first frame is done
+    pub fn fps(&self) -> u32 {
+        let total: Duration = self.samples.iter().map(|s| s.frame).sum();
+
+        if total == Duration::from_secs(0) {
+            return 0
+        }
+
+        ((self.samples.len() as f64) / total.as_secs_f64()).round() as u32
+    }
+
+    pub fn frame(&self) -> TimingSummary {
+        self.summary(|s| s.frame)
+    }
+
+    pub fn update(&self) -> TimingSummary {
+        self.summary(|s| s.update)
+    }
+
+    pub fn draw(&self) -> TimingSummary {
+        self.summary(|s| s.draw)
+    }
+
+    // Draw calls of the last frame
+    pub fn draw_calls(&self) -> u32 {
+        self.samples.back().map_or(0, |s| s.draw_calls)
+    }
+
+    fn summary<F>(&self, f: F) -> TimingSummary
+        where F: Fn(&FrameSample) -> Duration
+    {
+        let mut times: Vec<Duration> = self.samples.iter().map(f).collect();
+        times.sort();
+
+        if times.is_empty() {
+            let zero = Duration::from_secs(0);
+            return TimingSummary { min: zero, avg: zero, p95: zero, p99: zero, max: zero }
+        }
+
+        let total: Duration = times.iter().sum();
+
+        TimingSummary {
+            min: times[0],
+            avg: total / (times.len() as u32),
+            p95: percentile(&times, 95),
+            p99: percentile(&times, 99),
+            max: times[times.len() - 1],
+        }
+    }
+}
+
+// Nearest rank of the sorted times
+fn percentile(times: &[Duration], percent: usize) -> Duration {
+    let rank = (times.len() * percent).div_ceil(100);
+    times[rank.max(1) - 1]
+}
+
+fn target_ms(target_fps: u32) -> f64 {
+    let fps = if target_fps == 0 { GRAPH_UNLIMITED_FPS } else { target_fps };
+    1000.0 / (fps as f64)
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Shows the frame statistics on top of every screen
+pub struct PerfOverlay {
+    visible: bool,
+    lines: Vec<StaticText>,
+    // Frame times in milliseconds, oldest first
+    graph: Vec<f64>,
+    target_ms: f64,
+}
+
+impl PerfOverlay {
+    pub fn new() -> PerfOverlay {
+        PerfOverlay {
+            visible: false,
+            lines: (0..5).map(|i| StaticText::new(10, 10 + (i * 30), String::new())).collect(),
+            graph: Vec::new(),
+            target_ms: target_ms(0),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // The target is the frame rate limit of the settings, 0 is unlimited
+    pub fn update(&mut self, stats: &FrameStats, target_fps: u32) {
+        if !self.visible {
+            return
+        }
+
+        self.target_ms = target_ms(target_fps);
+
+        self.lines[0].set_text(format!("FPS: {}  DRAW CALLS: {}", stats.fps(), stats.draw_calls()));
+        self.lines[1].set_text(format!("{:<6}{:>5}{:>5}{:>5}{:>5}{:>5}", "MS", "MIN", "AVG", "P95", "P99", "MAX"));
+        self.lines[2].set_text(stats.frame().to_text("FRAME"));
+        self.lines[3].set_text(stats.update().to_text("UPDATE"));
+        self.lines[4].set_text(stats.draw().to_text("DRAW"));
+
+        self.graph = stats.samples.iter().map(|s| to_ms(s.frame)).collect();
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
+        if !self.visible {
+            return
+        }
+
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 192));
+        canvas.fill_rect(Some(Rect::new(0, 0, LOGICAL_WIDTH, (GRAPH_Y as u32) + 10))).unwrap();
+
+        for line in self.lines.iter() {
+            line.draw(canvas);
+        }
+
+        let bottom = GRAPH_Y;
+
+        for (i, ms) in self.graph.iter().enumerate() {
+            let height = ((ms * GRAPH_PIXELS_PER_MS) as u32).clamp(1, GRAPH_MAX_HEIGHT);
+            let x = GRAPH_X + (i as i32) * (GRAPH_BAR_WIDTH as i32);
+
+            if *ms > self.target_ms {
+                canvas.set_draw_color(Color::RGB(255, 64, 64));
+            } else {
+                canvas.set_draw_color(Color::RGB(64, 255, 64));
+            }
+
+            canvas.fill_rect(Some(Rect::new(x, bottom - (height as i32), GRAPH_BAR_WIDTH - 1, height))).unwrap();
+        }
+
+        let target_y = bottom - ((self.target_ms * GRAPH_PIXELS_PER_MS) as i32);
+        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        let end_x = GRAPH_X + (WINDOW_SIZE as i32) * (GRAPH_BAR_WIDTH as i32);
+        canvas.draw_line(Point::new(GRAPH_X, target_y), Point::new(end_x, target_y)).unwrap();
+
+        canvas.set_blend_mode(BlendMode::None);
+    }
+
+    pub fn set_font(&mut self, font: &Rc<Font>) {
+        for line in self.lines.iter_mut() {
+            line.set_font(font);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn sample(frame: u64) -> FrameSample {
+        FrameSample {
+            frame: ms(frame),
+            update: ms(frame / 2),
+            draw: ms(frame / 4),
+            draw_calls: frame as u32,
+        }
+    }
+
+    #[test]
+    fn percentile_nearest_rank() {
+        let times: Vec<Duration> = (1..=100).map(ms).collect();
+
+        assert_eq!(percentile(&times, 95), ms(95));
+        assert_eq!(percentile(&times, 99), ms(99));
+        assert_eq!(percentile(&times, 100), ms(100));
+        assert_eq!(percentile(&times[..10], 95), ms(10));
+        assert_eq!(percentile(&times[..1], 0), ms(1));
+    }
+
+    #[test]
+    fn summary_of_frames() {
+        let mut stats = FrameStats::new();
+
+        // Not sorted, like real frames
+        for frame in (1..=100).rev() {
+            stats.add(sample(frame));
+        }
+
+        let frame = stats.frame();
+        assert_eq!(frame.min, ms(1));
+        assert_eq!(frame.avg, Duration::from_micros(50_500));
+        assert_eq!(frame.p95, ms(95));
+        assert_eq!(frame.p99, ms(99));
+        assert_eq!(frame.max, ms(100));
+
+        assert_eq!(stats.update().max, ms(50));
+        assert_eq!(stats.draw().max, ms(25));
+        assert_eq!(stats.draw_calls(), 1);
+    }
+
+    #[test]
+    fn empty_stats() {
+        let stats = FrameStats::new();
+
+        assert_eq!(stats.fps(), 0);
+        assert_eq!(stats.draw_calls(), 0);
+        assert_eq!(stats.frame().max, Duration::from_secs(0));
+    }
+
+    #[test]
+    fn fps_over_the_window() {
+        let mut stats = FrameStats::new();
+
+        for _ in 0..30 {
+            stats.add(sample(20));
+        }
+        assert_eq!(stats.fps(), 50);
+
+        for _ in 0..30 {
+            stats.add(sample(10));
+        }
+        // 60 frames in 0.9 seconds
+        assert_eq!(stats.fps(), 67);
+    }
+
+    #[test]
+    fn oldest_sample_is_evicted() {
+        let mut stats = FrameStats::new();
+
+        for frame in 1..=(WINDOW_SIZE as u64) {
+            stats.add(sample(frame));
+        }
+        assert_eq!(stats.samples.len(), WINDOW_SIZE);
+        assert_eq!(stats.frame().min, ms(1));
+
+        stats.add(sample(500));
+        assert_eq!(stats.samples.len(), WINDOW_SIZE);
+        assert_eq!(stats.frame().min, ms(2));
+        assert_eq!(stats.frame().max, ms(500));
+        assert_eq!(stats.draw_calls(), 500);
+    }
+
+    #[test]
+    fn graph_target() {
+        assert_eq!(target_ms(50), 20.0);
+        assert_eq!(target_ms(0), 1000.0 / (GRAPH_UNLIMITED_FPS as f64));
+    }
+}
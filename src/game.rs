@@ -1,4 +1,6 @@
 // Rust modules
+use std::env;
+use std::mem;
 use std::path::Path;
 use std::time::{Instant, Duration};
 use std::thread;
@@ -10,50 +12,169 @@ use std::io::Error as StdIOError;
 // External modules
 use sdl2::render::{TextureCreator, Canvas};
 use sdl2::video::{Window, WindowContext, WindowBuildError};
-use sdl2::image::{LoadTexture, InitFlag};
-use sdl2::EventPump;
+use sdl2::image::{InitFlag};
 use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::IntegerOrSdlError;
+use serde_derive::{Serialize, Deserialize};
 
 // Local modules
 use crate::settings::{GameSettings};
-use crate::text_fx::{Font};
+use crate::text_fx::{Font, StaticText};
+use crate::screen::{Screen, ScreenContext, ScreenRegistry};
+use crate::render::{Renderer};
 use crate::main_menu::{MainMenu};
 use crate::audio_menu::{AudioMenu};
 use crate::gfx_menu::{GFXMenu};
-use crate::high_score::{HighScoreMenu};
+use crate::high_score::{self, HighScore, HighScoreMenu};
 use crate::credit_menu::{CreditMenu};
+use crate::controls_menu::{ControlsMenu};
+use crate::play_game::{PlayGame};
+use crate::pause_menu::{PauseMenu};
+use crate::level_editor::{LevelEditor};
+use crate::name_entry::{NameEntry};
+use crate::replay_viewer::{ReplayViewer};
+use crate::replay::{Replay};
+use crate::level_pack;
+use crate::video;
+use crate::input::{self, Input, Action};
+use crate::audio::{Audio};
+use crate::frame_stats::{self, FrameStats, FrameSample, PerfOverlay};
+use crate::event_source::{EventSource};
+
+// The game state is updated at a fixed rate, independent of the frame rate
+pub const UPDATE_STEP: Duration = Duration::from_micros(16_667);
+// Longer frames are cut, e.g. while the window is dragged, so the game
+// doesn't have to catch up with lots of updates
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+// How long the name of a new music track is shown
+const NOW_PLAYING_TIME: Duration = Duration::from_secs(3);
+
+// SDL can only be initialized once at a time,
+// so the tests that create a game take turns
+#[cfg(test)]
+pub static SDL_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
 pub struct Game {
     pub quit: bool,
     screen: GameScreen,
     settings: GameSettings,
-    main_menu: MainMenu,
-    audio_menu: AudioMenu,
-    gfx_menu: GFXMenu,
-    high_score_menu: HighScoreMenu,
-    credit_menu: CreditMenu,
-    frame_duration: i64,
-    fps: u32,
+    audio: Audio,
+    input: Input,
+    // Screen the current playlist was chosen for
+    music_screen: Option<GameScreenKind>,
+    now_playing: StaticText,
+    now_playing_start: Option<Instant>,
+    screens: ScreenRegistry,
+    high_score: HighScore,
+    // Score and replay of the last run until the player has entered a name
+    finished_run: Option<(u32, Replay)>,
+    // Replay to watch, picked in the high score table
+    replay: Option<Replay>,
+    // Only set when the canvas is created, changes need a restart
+    vsync: bool,
+    frame_stats: FrameStats,
+    perf_overlay: PerfOverlay,
     pub canvas: Canvas<Window>,
-    pub event_pump: EventPump,
+    event_source: Box<dyn EventSource>,
+    // Without a display and saved files, used to run scripts
+    headless: bool,
     texture_creator: TextureCreator<WindowContext>,
     fonts: Vec<Rc<Font>>,
+    // Integer scaling setting the textures were created with
+    texture_scaling: Option<bool>,
 }
 
 impl Game {
     pub fn new() -> Result<Game, GameError> {
+        Game::create(false)
+    }
+
+    // Uses the dummy drivers of SDL and the default settings,
+    // so a script gives the same result on every machine
+    pub fn new_headless() -> Result<Game, GameError> {
+        Game::create(true)
+    }
+
+    fn create(headless: bool) -> Result<Game, GameError> {
+        if headless {
+            env::set_var("SDL_VIDEODRIVER", "dummy");
+            env::set_var("SDL_AUDIODRIVER", "dummy");
+        }
+
+        // Touches are translated by the game itself, see input::touch_to_mouse()
+        sdl2::hint::set("SDL_TOUCH_MOUSE_EVENTS", "0");
+
         let sdl_context = sdl2::init()
             .map_err(|e| GameError::SDLInit(e))?;
         let video_subsystem = sdl_context.video()
             .map_err(|e| GameError::SDLVideo(e))?;
 
-        let window = video_subsystem.window("Mini-Magnets", 800, 600)
+        let audio = Audio::new(&sdl_context);
+        let input = Input::new(&sdl_context);
+
+        let mut settings = GameSettings::new();
+
+        if !headless {
+            if let Err(e) = settings.load() {
+                println!("Could not load settings ({}), using default", e);
+            }
+        }
+
+        let screen_mode = settings.get_screen_mode();
+        let window = video_subsystem.window("Mini-Magnets", screen_mode.width, screen_mode.height)
             .position_centered()
             .build()?;
 
-        let mut canvas = window.into_canvas().accelerated().build()?;
+        // The saved mode may not be available anymore, e.g. with another monitor
+        let screen_modes = video::query_screen_modes(&window);
+        settings.set_screen_mode(video::closest_screen_mode(&screen_modes, screen_mode));
+
+        // Vsync is only used when the canvas is created
+        let vsync = settings.get_vsync();
+
+        let mut gfx_menu = GFXMenu::new();
+        gfx_menu.set_screen_modes(screen_modes);
+        gfx_menu.set_active_vsync(vsync);
+
+        let mut play_game = PlayGame::new();
+
+        if let Err(e) = play_game.load_pack(level_pack::PACK_PATH) {
+            println!("Could not load level pack: {}", e);
+        }
+
+        let mut screens = ScreenRegistry::new();
+        screens.register(GameScreenKind::MainMenu, Box::new(MainMenu::new()));
+        screens.register(GameScreenKind::AudioMenu, Box::new(AudioMenu::new()));
+        screens.register(GameScreenKind::GFXMenu, Box::new(gfx_menu));
+        screens.register(GameScreenKind::ControlsMenu, Box::new(ControlsMenu::new()));
+        screens.register(GameScreenKind::HighScoreMenu, Box::new(HighScoreMenu::new()));
+        screens.register(GameScreenKind::CreditMenu, Box::new(CreditMenu::new()));
+        screens.register(GameScreenKind::PlayGame, Box::new(play_game));
+        screens.register(GameScreenKind::PauseMenu, Box::new(PauseMenu::new()));
+        screens.register(GameScreenKind::LevelEditor, Box::new(LevelEditor::new()));
+        screens.register(GameScreenKind::NameEntry, Box::new(NameEntry::new()));
+        screens.register(GameScreenKind::ReplayViewer, Box::new(ReplayViewer::new()));
+
+        // The dummy driver has no hardware renderer
+        let mut canvas_builder = if headless {
+            window.into_canvas().software()
+        } else {
+            window.into_canvas().accelerated()
+        };
+
+        if vsync {
+            canvas_builder = canvas_builder.present_vsync();
+        }
+
+        let mut canvas = canvas_builder.build()?;
+
+        if let Err(e) = video::apply_video_settings(&mut canvas, &settings) {
+            println!("Could not set video mode: {}", e);
+        }
+
         // let mut canvas = window.into_canvas().build().map_err(|e| GameError::SDLCanvas(e))?;
         let texture_creator = canvas.texture_creator();
 
@@ -71,40 +192,87 @@ impl Game {
         Ok(Game {
             quit: false,
             screen: GameScreen::new(),
-            settings: GameSettings::new(),
-            main_menu: MainMenu::new(),
-            audio_menu: AudioMenu::new(),
-            gfx_menu: GFXMenu::new(),
-            high_score_menu: HighScoreMenu::new(),
-            credit_menu: CreditMenu::new(),
-            frame_duration: 16,
-            fps: 0,
+            settings,
+            audio,
+            input,
+            music_screen: None,
+            now_playing: StaticText::new(10, 5, String::new()),
+            now_playing_start: None,
+            screens,
+            high_score: HighScore::new(),
+            finished_run: None,
+            replay: None,
+            vsync,
+            frame_stats: FrameStats::new(),
+            perf_overlay: PerfOverlay::new(),
             canvas: canvas,
-            event_pump: event_pump,
+            event_source: Box::new(event_pump),
+            headless,
             texture_creator: texture_creator,
             fonts: Vec::new(),
+            texture_scaling: None,
         })
     }
 
     pub fn run(&mut self) -> Result<(), GameError> {
         self.load_resources()?;
 
+        let mut previous = Instant::now();
+        let mut accumulator = Duration::from_secs(0);
+
         while !self.quit {
             let instant = Instant::now();
+            accumulator += (instant - previous).min(MAX_FRAME_TIME);
+            previous = instant;
 
             self.process();
-            self.update();
-            self.draw();
 
-            let sleep_time = self.frame_duration - (instant.elapsed().as_millis() as i64);
-            if sleep_time > 0 {
-                thread::sleep(Duration::from_millis(sleep_time as u64))
+            let update_start = Instant::now();
+
+            while accumulator >= UPDATE_STEP {
+                self.update();
+                accumulator -= UPDATE_STEP;
             }
 
-            self.calculate_fps(instant.elapsed().as_millis());
+            let update_time = update_start.elapsed();
+            let draw_start = Instant::now();
+
+            // The time left over is used to draw between two updates
+            self.draw(accumulator.as_secs_f64() / UPDATE_STEP.as_secs_f64());
+
+            // Waiting for vsync is not part of the draw time
+            let draw_time = draw_start.elapsed();
+            self.canvas.present();
+
+            self.limit_frame_rate(instant);
+
+            self.frame_stats.add(FrameSample {
+                frame: instant.elapsed(),
+                update: update_time,
+                draw: draw_time,
+                draw_calls: frame_stats::take_draw_calls(),
+            });
+            self.perf_overlay.update(&self.frame_stats, self.settings.get_target_fps());
         }
 
-        match self.high_score_menu.save() {
+        self.shut_down();
+
+        Ok(())
+    }
+
+    // Lets the open screens undo unconfirmed changes and saves everything, unless headless
+    pub fn shut_down(&mut self) {
+        for kind in self.screen.stack.clone().into_iter().rev() {
+            self.with_screen(kind, |screen, context| screen.quit(context));
+        }
+
+        self.audio.stop_music();
+
+        if self.headless {
+            return
+        }
+
+        match self.high_score.save(high_score::FILE_PATH) {
             Err(e) => {
                 println!("Could not save high score table: {}", e);
             },
@@ -116,198 +284,341 @@ impl Game {
             Err(e) => {
                 println!("Could not save settings: {}", e);
             },
-            _ => {                
+            _ => {
             }
         }
+    }
 
-        Ok(())
+    // Replaces the window events, e.g. with scripted input
+    pub fn set_event_source(&mut self, event_source: Box<dyn EventSource>) {
+        self.event_source = event_source;
+    }
+
+    // One frame with exactly one update, so scripts get the same result every time
+    pub fn step(&mut self) {
+        self.process();
+        self.update();
+        self.draw(0.0);
+        self.canvas.present();
+    }
+
+    // Opens the screens as if the player went there, every new screen is entered
+    pub fn show_screens(&mut self, stack: &[GameScreenKind]) {
+        let old_stack = mem::replace(&mut self.screen.stack, stack.to_vec());
+        self.change_screens(old_stack);
+    }
+
+    // The run the name entry asks for a name, without playing it first
+    pub fn set_finished_run(&mut self, score: u32, replay: Replay) {
+        self.finished_run = Some((score, replay));
+    }
+
+    // Replaces the font of every screen, e.g. with one for an offscreen renderer
+    pub fn set_font(&mut self, font: &Rc<Font>) {
+        self.screens.set_font(font);
+    }
+
+    // Only the screens, without the music name and the performance overlay
+    pub fn draw_screens(&self, renderer: &mut dyn Renderer, alpha: f64) {
+        draw_stack(&self.screens, &self.screen.stack, renderer, alpha);
+    }
+
+    pub fn current_screen(&self) -> GameScreenKind {
+        self.screen.current()
+    }
+
+    pub fn selected_item(&self) -> Option<usize> {
+        self.screens.get(self.screen.current()).selected_item()
+    }
+
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    pub fn is_quit(&self) -> bool {
+        self.quit
     }
 
     fn process(&mut self) {
-        for event in self.event_pump.poll_iter() {
+        // Collected first, processing needs the whole game mutably
+        let events = self.event_source.poll();
+
+        for event in events {
             match event {
                 Event::Quit {..} => {
                     // User closed main window, quit game
                     self.quit = true;
                 },
+                Event::KeyDown { keycode: Some(Keycode::F3), repeat: false, .. } => {
+                    // Frame statistics, not bindable and available on every screen
+                    self.perf_overlay.toggle();
+                },
                 _ => {
-                    match self.screen.current_screen {
-                        GameScreenKind::MainMenu => {
-                            self.main_menu.process(&event, &mut self.quit, &mut self.screen);
-                        },
-                        GameScreenKind::AudioMenu => {
-                            self.audio_menu.process(&event, &mut self.screen, &mut self.settings);
-                        },
-                        GameScreenKind::GFXMenu => {
-                            self.gfx_menu.process(&event, &mut self.screen, &mut self.settings);
-                        },
-                        GameScreenKind::HighScoreMenu => {
-                            self.high_score_menu.process(&event, &mut self.screen);
-                        },
-                        GameScreenKind::CreditMenu => {
-                            self.credit_menu.process(&event, &mut self.screen);
-                        },
-                        _ => {
-                            println!("Not implemented yet!");
-                            self.screen.current_screen = GameScreenKind::MainMenu;
-                        }
-                    }
+                    // Touches work like the left mouse button
+                    let event = input::touch_to_mouse(&event, |x, y| video::window_to_logical(&self.canvas, x, y))
+                        .unwrap_or(event);
+                    let action = self.input.translate(&event, self.settings.get_key_bindings());
+                    self.process_input(&event, action);
                 }
             }
         }
+
+        // Held controller buttons and sticks
+        if let Some((event, action)) = self.input.update() {
+            self.process_input(&event, Some(action));
+        }
+
+    }
+
+    fn process_input(&mut self, event: &Event, action: Option<Action>) {
+        let stack = self.screen.stack.clone();
+        self.with_screen(self.screen.current(), |screen, context| screen.process(event, action, context));
+        self.change_screens(stack);
     }
 
     fn update(&mut self) {
-        match self.screen.current_screen {
-            GameScreenKind::MainMenu => {
-                self.main_menu.update(self.fps);
-            },
-            GameScreenKind::AudioMenu => {
-                self.audio_menu.update();
-            },
-            GameScreenKind::GFXMenu => {
-                self.gfx_menu.update();
-            },
-            GameScreenKind::HighScoreMenu => {
-                self.high_score_menu.update();
-            },
-            GameScreenKind::CreditMenu => {
-                self.credit_menu.update();
-            },
-            _ => {
-                println!("Not implemented yet!");
-                self.screen.current_screen = GameScreenKind::MainMenu;
-            }
+        self.update_music();
+
+        if let Err(e) = self.update_texture_scaling() {
+            println!("Could not load the textures again: {}", e);
         }
+
+        let stack = self.screen.stack.clone();
+        self.with_screen(self.screen.current(), |screen, context| screen.update(context));
+        self.change_screens(stack);
     }
 
-    fn draw(&mut self) {
+    fn draw(&mut self, alpha: f64) {
         self.canvas.set_draw_color(Color::RGB(0, 0, 0));
         self.canvas.clear();
 
-        match self.screen.current_screen {
-            GameScreenKind::MainMenu => {
-                self.main_menu.draw(&mut self.canvas)
-            },
-            GameScreenKind::AudioMenu => {
-                self.audio_menu.draw(&mut self.canvas)
-            },
-            GameScreenKind::GFXMenu => {
-                self.gfx_menu.draw(&mut self.canvas)
-            },
-            GameScreenKind::HighScoreMenu => {
-                self.high_score_menu.draw(&mut self.canvas)
-            },
-            GameScreenKind::CreditMenu => {
-                self.credit_menu.draw(&mut self.canvas)
-            },
-            _ => {
-                println!("Not implemented yet!");
-                self.screen.current_screen = GameScreenKind::MainMenu;
-            }
+        draw_stack(&self.screens, &self.screen.stack, &mut self.canvas, alpha);
+
+        if self.now_playing_start.is_some() {
+            self.now_playing.draw(&mut self.canvas);
         }
 
-        self.canvas.present();
+        self.perf_overlay.draw(&mut self.canvas);
     }
 
-    fn calculate_fps(&mut self, elapsed: u128) {
-        let fps = 1000.0 / (elapsed as f64);
-        self.fps = (((self.fps as f64) + fps) / 2.0) as u32;
+    // Calls the function with the screen and everything it may change
+    fn with_screen<F>(&mut self, kind: GameScreenKind, f: F)
+        where F: FnOnce(&mut dyn Screen, &mut ScreenContext)
+    {
+        let mut context = ScreenContext {
+            screen: &mut self.screen,
+            settings: &mut self.settings,
+            audio: &mut self.audio,
+            canvas: &mut self.canvas,
+            high_score: &mut self.high_score,
+            finished_run: &mut self.finished_run,
+            replay: &mut self.replay,
+            device: self.input.get_device(),
+            fps: self.frame_stats.fps(),
+            quit: &mut self.quit,
+            headless: self.headless,
+        };
+
+        f(self.screens.get_mut(kind), &mut context);
     }
 
-    fn load_resources(&mut self) -> Result<(), GameError> {
-        self.load_font("assets/font2.png", 24, 24)?;
+    // Exits the screens that were closed and enters the new ones, one at a time,
+    // because entering or exiting a screen may open another one.
+    fn change_screens(&mut self, mut old_stack: Vec<GameScreenKind>) {
+        while old_stack != self.screen.stack {
+            let common = old_stack.iter().zip(self.screen.stack.iter())
+                .take_while(|(old, new)| old == new)
+                .count();
+
+            if old_stack.len() > common {
+                let closed = old_stack.pop().unwrap();
+                self.with_screen(closed, |screen, context| screen.exit(context));
+            } else {
+                let opened = self.screen.stack[common];
+                old_stack.push(opened);
+                self.with_screen(opened, |screen, context| screen.enter(context));
+            }
+        }
+    }
+
+    // Each screen has its playlist, switching screens fades out the old track
+    // and then fades in the new one
+    fn update_music(&mut self) {
+        if self.music_screen != Some(self.screen.current()) {
+            self.music_screen = Some(self.screen.current());
+            self.audio.play_playlist(&self.screens.get(self.screen.current()).playlist());
+        }
 
-        self.main_menu.set_font(&self.fonts[0]);
-        self.credit_menu.set_font(&self.fonts[0]);
-        self.high_score_menu.set_font(&self.fonts[0]);
-        self.audio_menu.set_font(&self.fonts[0]);
-        self.gfx_menu.set_font(&self.fonts[0]);
+        self.audio.update();
 
-        match self.high_score_menu.load() {
-            Err(e) => {
-                println!("Could not load high score talbe ({}), using default", e);
-            },
-            _ => {
+        if let Some(track) = self.audio.take_now_playing() {
+            self.now_playing.set_text(format!("MUSIC: {}", track.to_uppercase()));
+            self.now_playing_start = Some(Instant::now());
+        }
+
+        if let Some(start) = self.now_playing_start {
+            if start.elapsed() >= NOW_PLAYING_TIME {
+                self.now_playing_start = None;
             }
         }
+    }
 
-        match self.settings.load() {
-            Err(e) => {
-                println!("Could not load settings ({}), using default", e);
-            },
-            _ => {
+    // Sleeps for the rest of the frame, with vsync present() already waits
+    fn limit_frame_rate(&self, frame_start: Instant) {
+        let target_fps = self.settings.get_target_fps();
+
+        if self.vsync || target_fps == 0 {
+            return
+        }
+
+        let frame_duration = Duration::from_secs(1) / target_fps;
+
+        if let Some(sleep_time) = frame_duration.checked_sub(frame_start.elapsed()) {
+            thread::sleep(sleep_time);
+        }
+    }
+
+    pub fn load_resources(&mut self) -> Result<(), GameError> {
+        self.update_texture_scaling()?;
+
+        if !self.headless {
+            match self.high_score.load(high_score::FILE_PATH) {
+                Err(e) => {
+                    println!("Could not load high score talbe ({}), using default", e);
+                },
+                _ => {
+                }
             }
         }
 
-        self.audio_menu.update_settings(&self.settings);
-        self.gfx_menu.update_settings(&self.settings);
+        self.audio.load_resources();
+        self.audio.apply_settings(&self.settings);
+
+        // Enters the main menu
+        self.change_screens(Vec::new());
+
+        Ok(())
+    }
+
+    // The scale quality of a texture is fixed when it's created,
+    // so the textures are loaded again when the scaling has changed
+    fn update_texture_scaling(&mut self) -> Result<(), GameError> {
+        let integer_scaling = self.settings.get_integer_scaling();
+
+        if self.texture_scaling == Some(integer_scaling) {
+            return Ok(())
+        }
+
+        video::set_scale_quality(&self.settings);
+        self.fonts.clear();
+        self.load_font("assets/font2.png", 24, 24)?;
+        self.texture_scaling = Some(integer_scaling);
+
+        self.screens.set_font(&self.fonts[0]);
+        self.now_playing.set_font(&self.fonts[0]);
+        self.perf_overlay.set_font(&self.fonts[0]);
 
         Ok(())
     }
 
     fn load_font<T: AsRef<Path>>(&mut self, path: T, char_width: u32, char_height: u32) -> Result<(), GameError> {
-        let texture = self.texture_creator.load_texture(path)
+        let font = Font::load(&self.texture_creator, path, char_width, char_height)
             .map_err(|e| GameError::SDLTextureLoad(e))?;
-        let texture_properties = texture.query();
 
-        let font = Rc::new(Font {
-            width: char_width,
-            height: char_height,
-            rows: (texture_properties.height / char_height) as u8,
-            cols: (texture_properties.width / char_width) as u8,
-            texture: texture,
-        });
-
-        self.fonts.push(font);
+        self.fonts.push(Rc::new(font));
 
         Ok(())
     }
 }
 
+// Overlays are drawn on top of the screens below them
+fn draw_stack(screens: &ScreenRegistry, stack: &[GameScreenKind], renderer: &mut dyn Renderer, alpha: f64) {
+    let first = stack.iter().rposition(|kind| !screens.get(*kind).is_overlay()).unwrap_or(0);
+
+    for kind in stack[first..].iter() {
+        screens.get(*kind).draw(renderer, alpha);
+    }
+}
+
 #[derive(Debug)]
+// The screens in the order they were opened, the last one is shown.
+// Back returns to the previous screen, the main menu is always at the bottom.
 pub struct GameScreen {
-    current_screen: GameScreenKind,
+    stack: Vec<GameScreenKind>,
 }
 
 impl GameScreen {
     pub fn new() -> GameScreen {
         GameScreen {
-            current_screen: GameScreenKind::MainMenu,
+            stack: vec![GameScreenKind::MainMenu],
         }
     }
 
+    // Closes all screens on top of the main menu
     pub fn main_menu(&mut self) {
-        self.current_screen = GameScreenKind::MainMenu;
+        self.stack.truncate(1);
+    }
+
+    // Returns to the previous screen
+    pub fn back(&mut self) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        }
     }
 
     pub fn audio_options(&mut self) {
-        self.current_screen = GameScreenKind::AudioMenu;
+        self.push(GameScreenKind::AudioMenu);
     }
 
     pub fn gfx_options(&mut self) {
-        self.current_screen = GameScreenKind::GFXMenu;
+        self.push(GameScreenKind::GFXMenu);
     }
 
     pub fn controls(&mut self) {
-        self.current_screen = GameScreenKind::ControlsMenu;
+        self.push(GameScreenKind::ControlsMenu);
     }
 
     pub fn high_score(&mut self) {
-        self.current_screen = GameScreenKind::HighScoreMenu;
+        self.push(GameScreenKind::HighScoreMenu);
     }
 
     pub fn credit(&mut self) {
-        self.current_screen = GameScreenKind::CreditMenu;
+        self.push(GameScreenKind::CreditMenu);
     }
 
     pub fn start_game(&mut self) {
-        self.current_screen = GameScreenKind::PlayGame;
+        self.push(GameScreenKind::PlayGame);
+    }
+
+    pub fn pause(&mut self) {
+        self.push(GameScreenKind::PauseMenu);
+    }
+
+    pub fn level_editor(&mut self) {
+        self.push(GameScreenKind::LevelEditor);
+    }
+
+    pub fn name_entry(&mut self) {
+        self.push(GameScreenKind::NameEntry);
+    }
+
+    pub fn replay_viewer(&mut self) {
+        self.push(GameScreenKind::ReplayViewer);
+    }
+
+    fn current(&self) -> GameScreenKind {
+        *self.stack.last().unwrap()
+    }
+
+    fn push(&mut self, screen: GameScreenKind) {
+        if self.current() != screen {
+            self.stack.push(screen);
+        }
     }
 }
 
-#[derive(Debug)]
-enum GameScreenKind {
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameScreenKind {
     MainMenu,
     AudioMenu,
     GFXMenu,
@@ -315,6 +626,10 @@ enum GameScreenKind {
     HighScoreMenu,
     CreditMenu,
     PlayGame,
+    PauseMenu,
+    LevelEditor,
+    NameEntry,
+    ReplayViewer,
 }
 
 #[derive(Debug)]
@@ -1,110 +1,310 @@
 // Rust modules
 use std::rc::Rc;
+use std::time::{Instant, Duration};
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
 
 // Local modules
-use crate::game::{GameScreen};
-use crate::settings::{GameSettings};
+use crate::render::{Renderer};
+use crate::settings::{GameSettings, VideoSettings, ScreenMode};
+use crate::video;
 use crate::menu::{BaseMenu};
-use crate::text_fx::{Font};
+use crate::screen::{Screen, ScreenContext};
+use crate::text_fx::{Font, StaticText};
+use crate::input::{Action};
+
+// Time to confirm a new video mode before the previous one is restored
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct GFXMenu {
     base: BaseMenu,
+    confirm_text: StaticText,
+    // Modes supported by the display, smallest first
+    screen_modes: Vec<ScreenMode>,
+    // Mode to restore and start time of the countdown while waiting for confirmation
+    pending: Option<(VideoSettings, Instant)>,
+    mode_changed: bool,
+    scaling_changed: bool,
+    // Vsync of the running game, a change is only used after a restart
+    active_vsync: bool,
+    restart_text: StaticText,
+    restart_needed: bool,
 }
 
 impl GFXMenu {
     pub fn new() -> GFXMenu {
+        let mut base = BaseMenu::new(400, 100, 30, "GFX OPTIONS".to_string(), Vec::new(),
+            vec![
+                "FULLSCREEN:".to_string(),
+                "RESOLUTION:".to_string(),
+                "SCALING:".to_string(),
+                "VSYNC:".to_string(),
+                "FPS LIMIT:".to_string(),
+                "BACK".to_string()
+            ]);
+        base.set_value_items(vec![0, 1, 2, 3, 4]);
+
         GFXMenu {
-            base: BaseMenu::new(400, 100, 30, "GFX OPTIONS".to_string(), Vec::new(),
-                vec![
-                    "FULLSCREEN:".to_string(),
-                    "RESOLUTION:".to_string(),
-                    "BACK".to_string()
-                ]),
+            base,
+            confirm_text: StaticText::new(400, 400, String::new()),
+            screen_modes: Vec::new(),
+            pending: None,
+            mode_changed: false,
+            scaling_changed: false,
+            active_vsync: false,
+            restart_text: StaticText::new(400, 440, "VSYNC CHANGES NEED A RESTART".to_string()),
+            restart_needed: false,
+        }
+    }
+
+    fn update_settings(&mut self, settings: &GameSettings) {
+        self.base.change_menu(0, format!("FULLSCREEN: {}", settings.fullscreen_to_text()));
+        self.base.change_menu(1, format!("RESOLUTION: {}", settings.get_screen_mode().to_text()));
+        self.base.change_menu(2, format!("SCALING: {}", settings.scaling_to_text()));
+        self.base.change_menu(3, format!("VSYNC: {}", settings.vsync_to_text()));
+        self.base.change_menu(4, format!("FPS LIMIT: {}", settings.target_fps_to_text()));
+        self.restart_needed = settings.get_vsync() != self.active_vsync;
+    }
+
+    pub fn set_screen_modes(&mut self, screen_modes: Vec<ScreenMode>) {
+        self.screen_modes = screen_modes;
+    }
+
+    pub fn set_active_vsync(&mut self, vsync: bool) {
+        self.active_vsync = vsync;
+    }
+
+    fn change_vsync(&mut self, settings: &mut GameSettings) -> bool {
+        settings.toggle_vsync();
+        self.update_settings(settings);
+        true
+    }
+
+    // The limit is used right away by the game loop
+    fn change_target_fps(&mut self, settings: &mut GameSettings, step: i32) -> bool {
+        let changed = settings.change_target_fps(step);
+        self.update_settings(settings);
+        changed
+    }
+
+    // The scaling can't make the screen unusable, so there is no confirmation
+    fn change_scaling(&mut self, settings: &mut GameSettings) -> bool {
+        settings.toggle_integer_scaling();
+        self.scaling_changed = true;
+        self.update_settings(settings);
+        true
+    }
+
+    // Selects the next smaller (-1) or bigger (1) mode, stops at the ends of the list
+    fn change_screen_mode(&mut self, settings: &mut GameSettings, step: i32) -> bool {
+        if self.screen_modes.is_empty() {
+            return false
+        }
+
+        let current = video::closest_screen_mode(&self.screen_modes, settings.get_screen_mode());
+        let index = self.screen_modes.iter().position(|mode| *mode == current).unwrap_or(0) as i32;
+        let new_index = (index + step).max(0).min(self.screen_modes.len() as i32 - 1) as usize;
+        let new_mode = self.screen_modes[new_index];
+
+        self.change_mode(settings, |s| s.set_screen_mode(new_mode))
+    }
+
+    // True if the video settings have changed
+    fn change_mode<F: FnOnce(&mut GameSettings)>(&mut self, settings: &mut GameSettings, change: F) -> bool {
+        let old_video = settings.get_video();
+        change(settings);
+        let changed = old_video != settings.get_video();
+
+        if changed {
+            // Always revert to the last confirmed mode
+            let confirmed = match self.pending {
+                Some((confirmed, _)) => confirmed,
+                None => old_video,
+            };
+
+            self.pending = Some((confirmed, Instant::now()));
+            self.mode_changed = true;
+            self.update_settings(settings);
         }
+
+        changed
+    }
+
+    // Changes the window once the settings are changed
+    fn apply_video_settings(&mut self, context: &mut ScreenContext) {
+        if self.mode_changed {
+            self.mode_changed = false;
+
+            if let Err(e) = video::apply_video_settings(context.canvas, context.settings) {
+                println!("Could not change video mode: {}", e);
+            }
+        }
+
+        if self.scaling_changed {
+            self.scaling_changed = false;
+
+            if let Err(e) = video::apply_scaling(context.canvas, context.settings) {
+                println!("Could not change scaling: {}", e);
+            }
+        }
+    }
+
+    fn revert(&mut self, settings: &mut GameSettings) {
+        if let Some((confirmed, _)) = self.pending.take() {
+            settings.set_video(confirmed);
+            self.mode_changed = true;
+            self.update_settings(settings);
+        }
+    }
+}
+
+impl Screen for GFXMenu {
+    fn enter(&mut self, context: &mut ScreenContext) {
+        self.update_settings(context.settings);
     }
 
-    pub fn process(&mut self, event: &Event, game_screen: &mut GameScreen, settings: &mut GameSettings) {
-        match event {
-            Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let settings = &mut *context.settings;
+        let audio = &*context.audio;
+
+        let action = match self.base.process(event, action, audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        match action {
+            Action::Confirm if self.pending.is_some() => {
+                // Keep the new mode
+                self.pending = None;
+            },
+            Action::Back if self.pending.is_some() => {
+                self.base.back(audio);
+                self.revert(settings);
+            },
+            Action::Back => {
+                self.base.back(audio);
+                context.screen.back();
+            },
+            Action::Confirm => {
                 match self.base.get_selected() {
                     0 => {
-                        settings.toggle_fullscreen();
-                        // TODO: Change SDL fullscreen
-                        self.update_settings(settings);
+                        self.change_mode(settings, |s| s.toggle_fullscreen());
                     },
                     2 => {
-                        game_screen.main_menu();
+                        self.change_scaling(settings);
+                    },
+                    3 => {
+                        self.change_vsync(settings);
+                    },
+                    5 => {
+                        context.screen.back();
                     },
                     _ => {
                     }
                 }
             },
-            Event::KeyDown { keycode: Some(Keycode::Left), .. } => {
-                match self.base.get_selected() {
+            Action::Left => {
+                let changed = match self.base.get_selected() {
                     0 => {
-                        settings.toggle_fullscreen();
-                        // TODO: Change SDL fullscreen
-                        self.update_settings(settings);
+                        self.change_mode(settings, |s| s.toggle_fullscreen())
                     },
                     1 => {
-                        let old_res = settings.get_resolution();
-                        settings.dec_resolution();
-                        let new_res = settings.get_resolution();
-                        if old_res != new_res {
-                            // TODO: Change SDL resolution
-                            self.update_settings(settings);
-                        }
+                        self.change_screen_mode(settings, -1)
+                    },
+                    2 => {
+                        self.change_scaling(settings)
+                    },
+                    3 => {
+                        self.change_vsync(settings)
+                    },
+                    4 => {
+                        self.change_target_fps(settings, -1)
                     },
                     _ => {
+                        return
                     }
-                }
+                };
+
+                self.base.value_changed(audio, changed);
             },
-            Event::KeyDown { keycode: Some(Keycode::Right), .. } => {
-                match self.base.get_selected() {
+            Action::Right => {
+                let changed = match self.base.get_selected() {
                     0 => {
-                        settings.toggle_fullscreen();
-                        // TODO: Change SDL fullscreen
-                        self.update_settings(settings);
+                        self.change_mode(settings, |s| s.toggle_fullscreen())
                     },
                     1 => {
-                        let old_res = settings.get_resolution();
-                        settings.inc_resolution();
-                        let new_res = settings.get_resolution();
-                        if old_res != new_res {
-                            // TODO: Change SDL resolution
-                            self.update_settings(settings);
-                        }
+                        self.change_screen_mode(settings, 1)
+                    },
+                    2 => {
+                        self.change_scaling(settings)
+                    },
+                    3 => {
+                        self.change_vsync(settings)
+                    },
+                    4 => {
+                        self.change_target_fps(settings, 1)
                     },
                     _ => {
+                        return
                     }
-                }
+                };
+
+                self.base.value_changed(audio, changed);
             },
             _ => {
-                self.base.process(event);
             }
         }
+
+        self.apply_video_settings(context);
     }
 
-    pub fn update(&mut self) {
-        self.base.update()
+    // A mode that has not been confirmed is not saved
+    fn quit(&mut self, context: &mut ScreenContext) {
+        self.revert(context.settings);
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        self.base.draw(canvas)
+    fn update(&mut self, context: &mut ScreenContext) {
+        self.base.update();
+
+        if let Some((_, start)) = self.pending {
+            let elapsed = start.elapsed();
+
+            if elapsed >= CONFIRM_TIMEOUT {
+                self.revert(context.settings);
+            } else {
+                let seconds_left = (CONFIRM_TIMEOUT - elapsed).as_secs() + 1;
+                self.confirm_text.set_text(format!("KEEP THIS MODE? RETURN: YES ({})", seconds_left));
+                self.confirm_text.set_x(400);
+                self.confirm_text.center();
+            }
+        }
+
+        self.apply_video_settings(context);
     }
 
-    pub fn set_font(&mut self, font: &Rc<Font>) {
-        self.base.set_font(font);
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.base.draw(canvas, alpha);
+
+        if self.pending.is_some() {
+            self.confirm_text.draw(canvas);
+        }
+
+        if self.restart_needed {
+            self.restart_text.draw(canvas);
+        }
+    }
+
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.base.get_selected())
     }
 
-    pub fn update_settings(&mut self, settings: &GameSettings) {
-        self.base.change_menu(0, format!("FULLSCREEN: {}", if settings.get_fullscreen() { "ON" } else { "OFF" }));
-        self.base.change_menu(1, format!("RESOLUTION: {}", settings.resolution_to_text()));
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.base.set_font(font);
+        self.confirm_text.set_font(font);
+        self.restart_text.set_font(font);
+        self.restart_text.center();
     }
 }
@@ -0,0 +1,156 @@
+// Rust modules
+use std::fs;
+use std::path::Path;
+
+// External modules
+use sdl2::pixels::Color;
+
+// Local modules
+use crate::render::{Offscreen};
+use crate::game::{Game, GameScreenKind};
+use crate::replay::{Replay};
+use crate::video::{LOGICAL_WIDTH, LOGICAL_HEIGHT};
+
+pub const GOLDEN_DIR: &str = "assets/golden";
+
+// Same font as the game
+const FONT_PATH: &str = "assets/font2.png";
+
+// Color channels may differ a little, e.g. with another SDL version
+const TOLERANCE: u8 = 8;
+
+// Score shown by the name entry
+const NAME_ENTRY_SCORE: u32 = 1234;
+
+// Name of the image and the screens that are open, the last one is on top.
+// The level screens show the first level of the pack.
+const CASES: [(&str, &[GameScreenKind]); 10] = [
+    ("main_menu", &[GameScreenKind::MainMenu]),
+    ("audio_menu", &[GameScreenKind::MainMenu, GameScreenKind::AudioMenu]),
+    ("gfx_menu", &[GameScreenKind::MainMenu, GameScreenKind::GFXMenu]),
+    ("controls_menu", &[GameScreenKind::MainMenu, GameScreenKind::ControlsMenu]),
+    ("high_score", &[GameScreenKind::MainMenu, GameScreenKind::HighScoreMenu]),
+    ("credits", &[GameScreenKind::MainMenu, GameScreenKind::CreditMenu]),
+    ("level_editor", &[GameScreenKind::MainMenu, GameScreenKind::LevelEditor]),
+    ("play_game", &[GameScreenKind::MainMenu, GameScreenKind::PlayGame]),
+    ("pause_menu", &[GameScreenKind::MainMenu, GameScreenKind::PlayGame, GameScreenKind::PauseMenu]),
+    ("name_entry", &[GameScreenKind::MainMenu, GameScreenKind::NameEntry]),
+];
+
+// Draws every screen offscreen and compares it with its golden image.
+// The screens are opened in a headless game, so they show the default
+// settings and high scores just like the player sees them.
+// With update the golden images are written instead.
+// Returns false if any image is different or could not be read.
+pub fn check_screens<T: AsRef<Path>>(dir: T, update: bool) -> bool {
+    let dir = dir.as_ref();
+
+    let mut game = match Game::new_headless() {
+        Ok(game) => game,
+        Err(e) => {
+            println!("ERROR: could not create headless game: {}", e);
+            return false
+        }
+    };
+
+    if let Err(e) = game.load_resources() {
+        println!("ERROR: {}", e);
+        return false
+    }
+
+    let mut offscreen = match Offscreen::new(LOGICAL_WIDTH, LOGICAL_HEIGHT) {
+        Ok(offscreen) => offscreen,
+        Err(e) => {
+            println!("ERROR: could not create offscreen surface: {}", e);
+            return false
+        }
+    };
+
+    match offscreen.load_font(FONT_PATH, 24, 24) {
+        Ok(font) => {
+            game.set_font(&font);
+        },
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return false
+        }
+    }
+
+    if update {
+        if let Err(e) = fs::create_dir_all(dir) {
+            println!("ERROR: could not create '{}': {}", dir.display(), e);
+            return false
+        }
+    }
+
+    let mut valid = true;
+
+    for (name, stack) in CASES.iter() {
+        if stack.last() == Some(&GameScreenKind::NameEntry) {
+            game.set_finished_run(NAME_ENTRY_SCORE, Replay::new());
+        }
+
+        game.show_screens(stack);
+
+        let renderer = offscreen.renderer();
+        renderer.set_draw_color(Color::RGB(0, 0, 0));
+        renderer.clear();
+        game.draw_screens(renderer, 0.0);
+
+        let path = dir.join(format!("{}.png", name));
+
+        if update {
+            match offscreen.save_png(&path) {
+                Ok(_) => {
+                    println!("{}: updated", name);
+                },
+                Err(e) => {
+                    println!("{}: ERROR: could not save '{}': {}", name, path.display(), e);
+                    valid = false;
+                }
+            }
+            continue
+        }
+
+        match offscreen.compare(&path, TOLERANCE) {
+            Ok(diff) if diff.matches() => {
+                println!("{}: ok", name);
+            },
+            Ok(diff) => {
+                // Kept next to the golden image to look at the difference
+                let actual_path = dir.join(format!("{}.actual.png", name));
+                println!("{}: FAILED: {} pixels differ, max difference {}, see '{}'",
+                    name, diff.different_pixels, diff.max_difference, actual_path.display());
+
+                if let Err(e) = offscreen.save_png(&actual_path) {
+                    println!("    ERROR: could not save '{}': {}", actual_path.display(), e);
+                }
+
+                valid = false;
+            },
+            Err(e) => {
+                println!("{}: ERROR: could not compare with '{}': {}", name, path.display(), e);
+                valid = false;
+            }
+        }
+    }
+
+    valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{SDL_LOCK};
+
+    // Needs the game assets and the golden images, they are created with:
+    // cargo run -- --golden --update
+    // Then run with: cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn golden_images() {
+        let _lock = SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        assert!(check_screens(GOLDEN_DIR, false), "screens differ from the golden images, see the output above");
+    }
+}
@@ -6,102 +6,245 @@ use std::fmt;
 use std::io::Error as StdIOError;
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
 use serde_derive::{Serialize, Deserialize};
 use serde_json::error::Error as JSONError;
 
 // Local modules
-use crate::game::{GameScreen};
+use crate::render::{Renderer};
 use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
 use crate::text_fx::{Font};
+use crate::input::{Action};
+use crate::replay::{Replay};
+
+// Number of entries in the high score table
+pub const CAPACITY: usize = 10;
+pub const FILE_PATH: &str = "assets/highscore.json";
+
+// Older tables without replays are stored as [score, name]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HighScoreEntry {
+    pub score: u32,
+    pub name: String,
+    // Path of the replay file of the run
+    #[serde(default)]
+    pub replay: Option<String>,
+}
+
+impl HighScoreEntry {
+    fn new(score: u32, name: &str) -> HighScoreEntry {
+        HighScoreEntry {
+            score,
+            name: name.to_string(),
+            replay: None,
+        }
+    }
+
+    fn to_text(&self) -> String {
+        format!("{} - {}", self.score, self.name)
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct HighScore {
-    scores: Vec<(u32, String)>,
+    scores: Vec<HighScoreEntry>,
+    // Replays of entries that dropped out of the table, deleted when the table is saved
+    #[serde(skip)]
+    dropped: Vec<String>,
 }
 
 impl HighScore {
-    fn new() -> HighScore {
+    pub fn new() -> HighScore {
         HighScore {
             scores: vec![
-                (1000, "WILLI KAPPLER".to_string()),
-                 (900, "WILLI KAPPLER".to_string()),
-                 (800, "WILLI KAPPLER".to_string()),
-                 (700, "WILLI KAPPLER".to_string()),
-                 (600, "WILLI KAPPLER".to_string()),
-                 (500, "WILLI KAPPLER".to_string()),
-                 (400, "WILLI KAPPLER".to_string()),
-                 (300, "WILLI KAPPLER".to_string()),
-                 (200, "WILLI KAPPLER".to_string()),
-                 (100, "WILLI KAPPLER".to_string()),
+                HighScoreEntry::new(1000, "WILLI KAPPLER"),
+                HighScoreEntry::new(900, "WILLI KAPPLER"),
+                HighScoreEntry::new(800, "WILLI KAPPLER"),
+                HighScoreEntry::new(700, "WILLI KAPPLER"),
+                HighScoreEntry::new(600, "WILLI KAPPLER"),
+                HighScoreEntry::new(500, "WILLI KAPPLER"),
+                HighScoreEntry::new(400, "WILLI KAPPLER"),
+                HighScoreEntry::new(300, "WILLI KAPPLER"),
+                HighScoreEntry::new(200, "WILLI KAPPLER"),
+                HighScoreEntry::new(100, "WILLI KAPPLER"),
             ],
+            dropped: Vec::new(),
         }
     }
 
     fn to_text(&self) -> Vec<String> {
-        self.scores.iter().map(|(s, n)| format!("{} - {}", s, n)).collect::<Vec<String>>()
+        self.scores.iter().map(|entry| entry.to_text()).collect::<Vec<String>>()
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.scores.len() < CAPACITY || self.scores.iter().any(|entry| score > entry.score))
+    }
+
+    // Keeps the table sorted, a new score is placed below equal scores.
+    // The replays of entries that drop out of the table are deleted with the next save,
+    // so the saved table never refers to a deleted replay.
+    pub fn insert(&mut self, score: u32, name: String, replay: Option<String>) {
+        let index = self.scores.iter().position(|entry| score > entry.score).unwrap_or(self.scores.len());
+        self.scores.insert(index, HighScoreEntry { score, name, replay });
+
+        if self.scores.len() > CAPACITY {
+            let dropped = self.scores.split_off(CAPACITY);
+            self.dropped.extend(dropped.into_iter().filter_map(|entry| entry.replay));
+        }
+    }
+
+    // The entries that have a replay
+    pub fn with_replay(&self) -> Vec<&HighScoreEntry> {
+        self.scores.iter().filter(|entry| entry.replay.is_some()).collect()
+    }
+
+    pub fn load(&mut self, filepath: &str) -> Result<(), HighScoreError> {
+        let data = fs::read_to_string(filepath)
+            .map_err(|e| HighScoreError::ReadError(e, filepath.to_string()))?;
+        *self = serde_json::from_str(&data)?;
+
+        Ok(())
+    }
+
+    pub fn save(&mut self, filepath: &str) -> Result<(), HighScoreError> {
+        let data = serde_json::to_string(self)?;
+        fs::write(filepath, data)
+            .map_err(|e| HighScoreError::WriteError(e, filepath.to_string()))?;
+
+        for path in self.dropped.drain(..) {
+            if let Err(e) = fs::remove_file(&path) {
+                println!("Could not delete replay '{}': {}", path, e);
+            }
+        }
+
+        Ok(())
     }
 }
 
+// Shows the table, the scores are shared with the other screens.
+// The replay of an entry can be watched.
 pub struct HighScoreMenu {
     base: BaseMenu,
-    high_score: HighScore,
-    filepath: String,
+    // Text and path of the entries with a replay
+    replays: Vec<(String, String)>,
+    selected_replay: usize,
 }
 
 impl HighScoreMenu {
     pub fn new() -> HighScoreMenu {
-        let high_score = HighScore::new();
+        let mut base = BaseMenu::new(400, 100, 30, "HIGH SCORE".to_string(), HighScore::new().to_text(), vec![
+                "REPLAY: NONE".to_string(),
+                "BACK".to_string()
+            ]);
+        base.set_value_items(vec![0]);
 
         HighScoreMenu {
-            base: BaseMenu::new(400, 100, 30, "CREDITS".to_string(), high_score.to_text(), vec!["BACK".to_string()]),
-            high_score,
-            filepath: "assets/highscore.json".to_string(),
+            base,
+            replays: Vec::new(),
+            selected_replay: 0,
+        }
+    }
+
+    fn update_replay(&mut self) {
+        let text = match self.replays.get(self.selected_replay) {
+            Some((text, _)) => format!("REPLAY: {}", text),
+            None => "REPLAY: NONE".to_string(),
+        };
+
+        self.base.change_menu(0, text);
+    }
+
+    fn change_replay(&mut self, step: usize) -> bool {
+        if self.replays.is_empty() {
+            return false
         }
+
+        self.selected_replay = (self.selected_replay + step) % self.replays.len();
+        self.update_replay();
+        true
     }
 
-    pub fn process(&mut self, event: &Event, game_screen: &mut GameScreen) {
-        match event {
-            Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
-                game_screen.main_menu();
+    // Opens the replay viewer, an error sound if there is no replay
+    fn watch_replay(&mut self, context: &mut ScreenContext) {
+        let path = match self.replays.get(self.selected_replay) {
+            Some((_, path)) => path,
+            None => {
+                self.base.value_changed(context.audio, false);
+                return
+            }
+        };
+
+        match Replay::load(path) {
+            Ok(replay) => {
+                *context.replay = Some(replay);
+                context.screen.replay_viewer();
             },
-            _ => {
-                self.base.process(event);
+            Err(e) => {
+                println!("Could not load replay: {}", e);
+                self.base.value_changed(context.audio, false);
             }
         }
     }
+}
 
-    pub fn update(&mut self) {
-        self.base.update()
+impl Screen for HighScoreMenu {
+    fn enter(&mut self, context: &mut ScreenContext) {
+        self.base.set_text(context.high_score.to_text());
+        self.replays = context.high_score.with_replay().iter()
+            .map(|entry| (entry.to_text(), entry.replay.clone().unwrap_or_default()))
+            .collect();
+        self.selected_replay = 0;
+        self.update_replay();
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        self.base.draw(canvas)
-    }
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let action = match self.base.process(event, action, context.audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
 
-    pub fn set_font(&mut self, font: &Rc<Font>) {
-        self.base.set_font(font);
-        self.base.set_text(self.high_score.to_text());
+        match (action, self.base.get_selected()) {
+            (Action::Confirm, 0) => {
+                self.watch_replay(context);
+            },
+            (Action::Confirm, _) => {
+                context.screen.back();
+            },
+            (Action::Left, 0) => {
+                let len = self.replays.len();
+                let changed = self.change_replay(len.saturating_sub(1));
+                self.base.value_changed(context.audio, changed);
+            },
+            (Action::Right, 0) => {
+                let changed = self.change_replay(1);
+                self.base.value_changed(context.audio, changed);
+            },
+            (Action::Back, _) => {
+                self.base.back(context.audio);
+                context.screen.back();
+            },
+            _ => {
+            }
+        }
     }
 
-    pub fn load(&mut self) -> Result<(), HighScoreError> {
-        let data = fs::read_to_string(&self.filepath)
-            .map_err(|e| HighScoreError::ReadError(e, self.filepath.clone()))?;
-        self.high_score = serde_json::from_str(&data)?;
-        self.base.set_text(self.high_score.to_text());
+    fn update(&mut self, _context: &mut ScreenContext) {
+        self.base.update()
+    }
 
-        Ok(())
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.base.draw(canvas, alpha)
     }
 
-    pub fn save(&self) -> Result<(), HighScoreError> {
-        let data = serde_json::to_string(&self.high_score)?;
-        fs::write(&self.filepath, data)
-            .map_err(|e| HighScoreError::WriteError(e, self.filepath.clone()))?;
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.base.get_selected())
+    }
 
-        Ok(())
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.base.set_font(font);
     }
 }
 
@@ -149,3 +292,116 @@ impl From<JSONError> for HighScoreError {
         HighScoreError::ParseError(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn table(scores: &[u32]) -> HighScore {
+        HighScore {
+            scores: scores.iter().map(|score| HighScoreEntry::new(*score, "OLD")).collect(),
+            dropped: Vec::new(),
+        }
+    }
+
+    fn scores(high_score: &HighScore) -> Vec<(u32, &str)> {
+        high_score.scores.iter().map(|entry| (entry.score, entry.name.as_str())).collect()
+    }
+
+    #[test]
+    fn qualifies_for_full_table() {
+        let high_score = HighScore::new();
+
+        assert!(high_score.qualifies(1001));
+        assert!(high_score.qualifies(101));
+        // Must be better than the last entry
+        assert!(!high_score.qualifies(100));
+        assert!(!high_score.qualifies(50));
+    }
+
+    #[test]
+    fn qualifies_for_table_with_room() {
+        let high_score = table(&[300, 200]);
+
+        assert!(high_score.qualifies(1));
+        assert!(high_score.qualifies(200));
+    }
+
+    #[test]
+    fn zero_never_qualifies() {
+        assert!(!table(&[]).qualifies(0));
+        assert!(!HighScore::new().qualifies(0));
+    }
+
+    #[test]
+    fn insert_keeps_order() {
+        let mut high_score = table(&[300, 100]);
+        high_score.insert(200, "NEW".to_string(), None);
+        high_score.insert(400, "TOP".to_string(), None);
+        high_score.insert(50, "LAST".to_string(), None);
+
+        assert_eq!(scores(&high_score), vec![(400, "TOP"), (300, "OLD"), (200, "NEW"), (100, "OLD"), (50, "LAST")]);
+    }
+
+    #[test]
+    fn insert_tie_goes_below() {
+        let mut high_score = table(&[300, 200, 200, 100]);
+        high_score.insert(200, "NEW".to_string(), None);
+
+        assert_eq!(scores(&high_score), vec![(300, "OLD"), (200, "OLD"), (200, "OLD"), (200, "NEW"), (100, "OLD")]);
+    }
+
+    #[test]
+    fn insert_into_full_table() {
+        let mut high_score = HighScore::new();
+        high_score.insert(550, "NEW".to_string(), None);
+
+        assert_eq!(high_score.scores.len(), CAPACITY);
+        assert_eq!(high_score.scores[5].score, 550);
+        assert_eq!(high_score.scores[5].name, "NEW");
+        assert_eq!(high_score.scores[CAPACITY - 1].score, 200);
+
+        // A tie with the last entry drops out again
+        high_score.insert(200, "TIE".to_string(), None);
+        assert_eq!(high_score.scores.len(), CAPACITY);
+        assert!(high_score.scores.iter().all(|entry| entry.name != "TIE"));
+    }
+
+    #[test]
+    fn insert_zero() {
+        let mut high_score = table(&[100]);
+        high_score.insert(0, "ZERO".to_string(), None);
+
+        assert_eq!(scores(&high_score), vec![(100, "OLD"), (0, "ZERO")]);
+    }
+
+    #[test]
+    fn dropped_replay_is_deleted_on_save() {
+        let dir = std::env::temp_dir();
+        let replay = dir.join("mini_magnets_dropped_replay.json").display().to_string();
+        let table = dir.join("mini_magnets_high_score.json").display().to_string();
+        fs::write(&replay, "{}").unwrap();
+
+        let mut high_score = HighScore::new();
+        high_score.scores[CAPACITY - 1].replay = Some(replay.clone());
+        high_score.insert(2000, "NEW".to_string(), None);
+
+        // Still referred to by the table on disk
+        assert!(Path::new(&replay).exists());
+
+        high_score.save(&table).unwrap();
+        fs::remove_file(&table).unwrap();
+
+        assert!(!Path::new(&replay).exists());
+        assert!(high_score.dropped.is_empty());
+    }
+
+    #[test]
+    fn old_format_loads() {
+        let high_score: HighScore = serde_json::from_str("{\"scores\": [[10, \"A\"]]}").unwrap();
+
+        assert_eq!(scores(&high_score), vec![(10, "A")]);
+        assert!(high_score.scores[0].replay.is_none());
+    }
+}
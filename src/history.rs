@@ -0,0 +1,169 @@
+// Rust modules
+use std::collections::VecDeque;
+use std::mem;
+
+// External modules
+
+// Local modules
+use crate::board::{Board, Tile, Piece};
+
+// Memory used for all undo snapshots of a level
+const HISTORY_MEMORY: usize = 4 * 1024 * 1024;
+
+// Undo / redo history of board snapshots.
+// The number of snapshots is limited by the memory a single board needs,
+// if the limit is reached the oldest snapshot is dropped.
+pub struct History {
+    undo: VecDeque<Board>,
+    redo: Vec<Board>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity: 1,
+        }
+    }
+
+    // Clears the history and adapts the capacity to the size of the new board
+    pub fn reset(&mut self, board: &Board) {
+        let cells = (board.get_width() * board.get_height()) as usize;
+        let snapshot_size = mem::size_of::<Board>() +
+            cells * (mem::size_of::<Tile>() + mem::size_of::<Option<Piece>>());
+
+        self.capacity = (HISTORY_MEMORY / snapshot_size).max(1);
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // Stores the board state before a move, a new move invalidates the redo list
+    pub fn push(&mut self, board: Board) {
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+
+        self.undo.push_back(board);
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, current: &Board) -> Option<Board> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current.clone());
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: &Board) -> Option<Board> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current.clone());
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Direction, Tile};
+    use crate::replay::{ReplayState, ReplayInput};
+
+    // Boards that only differ in the player position
+    fn board(x: u32) -> Board {
+        let mut board = Board::new(8, 1);
+        board.set_player((x, 0));
+        board
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut history = History::new();
+        history.reset(&board(0));
+
+        history.push(board(0));
+        history.push(board(1));
+
+        assert_eq!(history.undo(&board(2)), Some(board(1)));
+        assert_eq!(history.undo(&board(1)), Some(board(0)));
+        assert_eq!(history.redo(&board(0)), Some(board(1)));
+        assert_eq!(history.redo(&board(1)), Some(board(2)));
+        assert_eq!(history.redo(&board(2)), None);
+    }
+
+    #[test]
+    fn undo_on_empty_history_is_a_no_op() {
+        let mut history = History::new();
+        history.reset(&board(0));
+
+        assert_eq!(history.undo(&board(0)), None);
+        // The current board must not end up in the redo list
+        assert_eq!(history.redo(&board(0)), None);
+    }
+
+    #[test]
+    fn new_move_clears_redo() {
+        let mut history = History::new();
+        history.reset(&board(0));
+
+        history.push(board(0));
+        history.push(board(1));
+        assert_eq!(history.undo(&board(2)), Some(board(1)));
+
+        history.push(board(1));
+        assert_eq!(history.redo(&board(3)), None);
+        assert_eq!(history.undo(&board(3)), Some(board(1)));
+    }
+
+    #[test]
+    fn memory_bound_drops_oldest_snapshot() {
+        let mut history = History::new();
+        history.reset(&board(0));
+        history.capacity = 3;
+
+        for x in 0..5 {
+            history.push(board(x));
+        }
+
+        assert_eq!(history.undo(&board(5)), Some(board(4)));
+        assert_eq!(history.undo(&board(4)), Some(board(3)));
+        assert_eq!(history.undo(&board(3)), Some(board(2)));
+        assert_eq!(history.undo(&board(2)), None);
+    }
+
+    #[test]
+    fn capacity_depends_on_board_size() {
+        let mut history = History::new();
+
+        history.reset(&Board::new(8, 8));
+        let small = history.capacity;
+        history.reset(&Board::new(64, 64));
+        let large = history.capacity;
+
+        assert!(small > large);
+        assert!(large >= 1);
+
+        history.reset(&Board::new(2048, 2048));
+        assert_eq!(history.capacity, 1);
+    }
+
+    #[test]
+    fn undo_count_is_separate_from_moves() {
+        let mut start = board(0);
+        start.set_tile((7, 0), Tile::Goal);
+        let mut state = ReplayState::new(start);
+
+        state.apply(ReplayInput::Move(Direction::Right));
+        state.apply(ReplayInput::Move(Direction::Right));
+        state.apply(ReplayInput::Undo);
+        assert_eq!((state.moves, state.undos), (1, 1));
+
+        state.apply(ReplayInput::Redo);
+        assert_eq!((state.moves, state.undos), (2, 1));
+
+        // Nothing to undo, neither counter changes
+        state.apply(ReplayInput::Restart);
+        state.apply(ReplayInput::Undo);
+        assert_eq!((state.moves, state.undos), (0, 1));
+        assert_eq!(state.board.get_player(), (0, 0));
+    }
+}
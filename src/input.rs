@@ -0,0 +1,479 @@
+// Rust modules
+use std::time::{Duration, Instant};
+
+// External modules
+use sdl2::Sdl;
+use sdl2::GameControllerSubsystem;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::{MouseButton, MouseState};
+use sdl2::controller::{Axis, Button, GameController};
+use serde_derive::{Serialize, Deserialize};
+
+// Local modules
+use crate::key_bindings::{KeyBindings};
+
+// Logical input, independent of the device that created it.
+// The directions are used for the menus and for moving the player.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Undo,
+    Redo,
+    Restart,
+    Pause,
+}
+
+impl Action {
+    // Held keys repeat these actions, all others only trigger once
+    pub fn repeats(self) -> bool {
+        matches!(self, Action::Up | Action::Down | Action::Left | Action::Right | Action::Undo | Action::Redo)
+    }
+}
+
+// Stick values below this are ignored, the full range is 32767
+const STICK_DEADZONE: i16 = 12_000;
+// Held controller buttons and sticks repeat their action
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const REPEAT_INTERVAL: Duration = Duration::from_millis(120);
+
+// Mouse state of a finger on the screen: the left button is held
+const TOUCH_BUTTON_STATE: u32 = 1;
+
+// The kind of device the player used last
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Device {
+    Keyboard,
+    Controller,
+}
+
+// Controller button for an action as shown in the menus
+pub fn controller_glyph(action: Action) -> &'static str {
+    match action {
+        Action::Up => "D-PAD UP",
+        Action::Down => "D-PAD DOWN",
+        Action::Left => "D-PAD LEFT",
+        Action::Right => "D-PAD RIGHT",
+        Action::Confirm => "(A)",
+        Action::Back => "(B)",
+        Action::Undo => "(X)",
+        Action::Redo => "(Y)",
+        Action::Restart => "(BACK)",
+        Action::Pause => "(START)",
+    }
+}
+
+// Keys that work even if they are not bound, so the menus can always be used
+fn fixed_key_action(key: Keycode) -> Option<Action> {
+    match key {
+        Keycode::Return | Keycode::KpEnter => Some(Action::Confirm),
+        Keycode::Escape => Some(Action::Back),
+        Keycode::Up => Some(Action::Up),
+        Keycode::Down => Some(Action::Down),
+        Keycode::Left => Some(Action::Left),
+        Keycode::Right => Some(Action::Right),
+        _ => None,
+    }
+}
+
+fn controller_action(button: Button) -> Option<Action> {
+    match button {
+        Button::DPadUp => Some(Action::Up),
+        Button::DPadDown => Some(Action::Down),
+        Button::DPadLeft => Some(Action::Left),
+        Button::DPadRight => Some(Action::Right),
+        Button::A => Some(Action::Confirm),
+        Button::B => Some(Action::Back),
+        Button::X => Some(Action::Undo),
+        Button::Y => Some(Action::Redo),
+        Button::Back => Some(Action::Restart),
+        Button::Start => Some(Action::Pause),
+        _ => None,
+    }
+}
+
+// Touches become left mouse button events at the logical position,
+// so everything that works with the mouse also works on a touch screen
+pub fn touch_to_mouse<F>(event: &Event, to_logical: F) -> Option<Event>
+    where F: Fn(f32, f32) -> (i32, i32)
+{
+    match *event {
+        Event::FingerDown { timestamp, x, y, .. } => {
+            let (x, y) = to_logical(x, y);
+            Some(Event::MouseButtonDown { timestamp, window_id: 0, which: 0,
+                mouse_btn: MouseButton::Left, clicks: 1, x, y })
+        },
+        Event::FingerUp { timestamp, x, y, .. } => {
+            let (x, y) = to_logical(x, y);
+            Some(Event::MouseButtonUp { timestamp, window_id: 0, which: 0,
+                mouse_btn: MouseButton::Left, clicks: 1, x, y })
+        },
+        Event::FingerMotion { timestamp, x, y, .. } => {
+            // The relative motion is not used anywhere
+            let (x, y) = to_logical(x, y);
+            Some(Event::MouseMotion { timestamp, window_id: 0, which: 0,
+                mousestate: MouseState::from_sdl_state(TOUCH_BUTTON_STATE), x, y, xrel: 0, yrel: 0 })
+        },
+        _ => None,
+    }
+}
+
+// A controller action that is repeated while the button or stick is held
+struct Held {
+    event: Event,
+    action: Action,
+    next_repeat: Instant,
+}
+
+// Translates SDL events into actions and keeps track of the connected controllers
+pub struct Input {
+    controller_subsystem: Option<GameControllerSubsystem>,
+    controllers: Vec<GameController>,
+    stick_x: i16,
+    stick_y: i16,
+    held: Option<Held>,
+    device: Device,
+}
+
+impl Input {
+    pub fn new(sdl_context: &Sdl) -> Input {
+        // Connected controllers are reported as added devices with the first events
+        let controller_subsystem = sdl_context.game_controller()
+            .map_err(|e| println!("Could not initialize game controllers: {}", e))
+            .ok();
+
+        Input {
+            controller_subsystem,
+            controllers: Vec::new(),
+            stick_x: 0,
+            stick_y: 0,
+            held: None,
+            device: Device::Keyboard,
+        }
+    }
+
+    // The action for an SDL event, if there is one.
+    // Bound keys win over the fixed navigation keys.
+    pub fn translate(&mut self, event: &Event, bindings: &KeyBindings) -> Option<Action> {
+        match *event {
+            Event::KeyDown { keycode: Some(key), repeat, .. } => {
+                self.set_device(Device::Keyboard);
+                let action = bindings.action_for(key).or_else(|| fixed_key_action(key))?;
+
+                if repeat && !action.repeats() {
+                    None
+                } else {
+                    Some(action)
+                }
+            },
+            Event::MouseButtonDown { mouse_btn: MouseButton::Right, .. } => {
+                self.set_device(Device::Keyboard);
+                Some(Action::Back)
+            },
+            Event::ControllerDeviceAdded { which, .. } => {
+                self.add_controller(which);
+                None
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.remove_controller(which);
+                None
+            },
+            Event::ControllerButtonDown { button, .. } => {
+                self.set_device(Device::Controller);
+                let action = controller_action(button)?;
+                self.hold(event, action);
+                Some(action)
+            },
+            Event::ControllerButtonUp { button, .. } => {
+                let released = controller_action(button);
+
+                if released.is_some() && self.held.as_ref().map(|held| held.action) == released {
+                    self.held = None;
+                }
+                None
+            },
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                self.process_stick(event, axis, value)
+            },
+            _ => None,
+        }
+    }
+
+    // Repeats the held controller action, returns the event that started it
+    pub fn update(&mut self) -> Option<(Event, Action)> {
+        let held = self.held.as_mut()?;
+        let now = Instant::now();
+
+        if !held.action.repeats() || now < held.next_repeat {
+            return None
+        }
+
+        held.next_repeat = now + REPEAT_INTERVAL;
+        Some((held.event.clone(), held.action))
+    }
+
+    pub fn get_device(&self) -> Device {
+        self.device
+    }
+
+    fn set_device(&mut self, device: Device) {
+        self.device = device;
+    }
+
+    fn hold(&mut self, event: &Event, action: Action) {
+        self.held = Some(Held {
+            event: event.clone(),
+            action,
+            next_repeat: Instant::now() + REPEAT_DELAY,
+        });
+    }
+
+    // The left stick works like the D-pad
+    fn process_stick(&mut self, event: &Event, axis: Axis, value: i16) -> Option<Action> {
+        let old_direction = self.stick_direction();
+
+        match axis {
+            Axis::LeftX => {
+                self.stick_x = value;
+            },
+            Axis::LeftY => {
+                self.stick_y = value;
+            },
+            _ => {
+                return None
+            }
+        }
+
+        let new_direction = self.stick_direction();
+
+        if new_direction == old_direction {
+            return None
+        }
+
+        match new_direction {
+            Some(action) => {
+                self.set_device(Device::Controller);
+                self.hold(event, action);
+            },
+            None => {
+                self.held = None;
+            }
+        }
+
+        new_direction
+    }
+
+    // The axis with the bigger deflection wins, the y axis points down
+    fn stick_direction(&self) -> Option<Action> {
+        let x = self.stick_x as i32;
+        let y = self.stick_y as i32;
+
+        if x.abs().max(y.abs()) < STICK_DEADZONE as i32 {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x < 0 { Action::Left } else { Action::Right })
+        } else {
+            Some(if y < 0 { Action::Up } else { Action::Down })
+        }
+    }
+
+    fn add_controller(&mut self, joystick_index: u32) {
+        let subsystem = match &self.controller_subsystem {
+            Some(subsystem) => subsystem,
+            None => {
+                return
+            }
+        };
+
+        match subsystem.open(joystick_index) {
+            Ok(controller) => {
+                let id = controller.instance_id();
+
+                if self.controllers.iter().all(|other| other.instance_id() != id) {
+                    println!("Controller connected: {}", controller.name());
+                    self.controllers.push(controller);
+                }
+            },
+            Err(e) => {
+                println!("Could not open controller {}: {}", joystick_index, e);
+            }
+        }
+    }
+
+    fn remove_controller(&mut self, instance_id: i32) {
+        if let Some(index) = self.controllers.iter().position(|controller| controller.instance_id() == instance_id) {
+            println!("Controller disconnected: {}", self.controllers[index].name());
+            self.controllers.remove(index);
+        }
+
+        if self.controllers.is_empty() {
+            self.held = None;
+            self.stick_x = 0;
+            self.stick_y = 0;
+            self.set_device(Device::Keyboard);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::Mod;
+    use sdl2::mouse::MouseWheelDirection;
+
+    // Without the controller subsystem, so no SDL context is needed
+    fn input() -> Input {
+        Input {
+            controller_subsystem: None,
+            controllers: Vec::new(),
+            stick_x: 0,
+            stick_y: 0,
+            held: None,
+            device: Device::Keyboard,
+        }
+    }
+
+    fn key(keycode: Keycode, repeat: bool) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None,
+            keymod: Mod::NOMOD, repeat }
+    }
+
+    fn button(button: Button) -> Event {
+        Event::ControllerButtonDown { timestamp: 0, which: 0, button }
+    }
+
+    fn stick(axis: Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion { timestamp: 0, which: 0, axis, value }
+    }
+
+    #[test]
+    fn bound_key_wins_over_fixed_key() {
+        let mut input = input();
+        let mut bindings = KeyBindings::default();
+
+        assert_eq!(input.translate(&key(Keycode::Z, false), &bindings), Some(Action::Undo));
+        assert_eq!(input.translate(&key(Keycode::Left, false), &bindings), Some(Action::Left));
+
+        bindings.set(Action::Left, Keycode::A).unwrap();
+        bindings.set(Action::Undo, Keycode::Left).unwrap();
+        assert_eq!(input.translate(&key(Keycode::Left, false), &bindings), Some(Action::Undo));
+        assert_eq!(input.translate(&key(Keycode::Z, false), &bindings), None);
+
+        // The fixed keys still work after the directions are bound to other keys
+        bindings.set(Action::Up, Keycode::W).unwrap();
+        assert_eq!(input.translate(&key(Keycode::W, false), &bindings), Some(Action::Up));
+        assert_eq!(input.translate(&key(Keycode::Up, false), &bindings), Some(Action::Up));
+        assert_eq!(input.translate(&key(Keycode::Return, false), &bindings), Some(Action::Confirm));
+        assert_eq!(input.translate(&key(Keycode::Escape, false), &bindings), Some(Action::Back));
+    }
+
+    #[test]
+    fn key_repeat_only_for_repeating_actions() {
+        let mut input = input();
+        let bindings = KeyBindings::default();
+
+        assert_eq!(input.translate(&key(Keycode::Down, true), &bindings), Some(Action::Down));
+        assert_eq!(input.translate(&key(Keycode::Z, true), &bindings), Some(Action::Undo));
+        assert_eq!(input.translate(&key(Keycode::Return, true), &bindings), None);
+        assert_eq!(input.translate(&key(Keycode::R, true), &bindings), None);
+        assert_eq!(input.translate(&key(Keycode::P, true), &bindings), None);
+    }
+
+    #[test]
+    fn wheel_is_left_to_the_menus() {
+        let mut input = input();
+        let wheel = Event::MouseWheel { timestamp: 0, window_id: 0, which: 0, x: 0, y: 1,
+            direction: MouseWheelDirection::Normal };
+
+        assert_eq!(input.translate(&wheel, &KeyBindings::default()), None);
+    }
+
+    #[test]
+    fn held_button_repeats() {
+        let mut input = input();
+        let bindings = KeyBindings::default();
+
+        assert_eq!(input.translate(&button(Button::DPadDown), &bindings), Some(Action::Down));
+        assert_eq!(input.get_device(), Device::Controller);
+
+        // Nothing before the delay is over
+        assert!(input.update().is_none());
+
+        input.held.as_mut().unwrap().next_repeat = Instant::now();
+        let (_, action) = input.update().unwrap();
+        assert_eq!(action, Action::Down);
+
+        // The next repeat comes after the interval
+        let next_repeat = input.held.as_ref().unwrap().next_repeat;
+        assert!(next_repeat > Instant::now() + REPEAT_INTERVAL / 2);
+        assert!(next_repeat <= Instant::now() + REPEAT_INTERVAL);
+        assert!(input.update().is_none());
+
+        let release = Event::ControllerButtonUp { timestamp: 0, which: 0, button: Button::DPadDown };
+        assert_eq!(input.translate(&release, &bindings), None);
+        assert!(input.held.is_none());
+    }
+
+    #[test]
+    fn held_confirm_does_not_repeat() {
+        let mut input = input();
+
+        assert_eq!(input.translate(&button(Button::A), &KeyBindings::default()), Some(Action::Confirm));
+        input.held.as_mut().unwrap().next_repeat = Instant::now();
+        assert!(input.update().is_none());
+    }
+
+    #[test]
+    fn stick_deadzone() {
+        let mut input = input();
+
+        assert_eq!(input.stick_direction(), None);
+
+        input.stick_x = STICK_DEADZONE - 1;
+        input.stick_y = -(STICK_DEADZONE - 1);
+        assert_eq!(input.stick_direction(), None);
+
+        input.stick_x = STICK_DEADZONE;
+        input.stick_y = 0;
+        assert_eq!(input.stick_direction(), Some(Action::Right));
+    }
+
+    #[test]
+    fn stick_dominant_axis() {
+        let mut input = input();
+
+        input.stick_x = -20_000;
+        input.stick_y = 15_000;
+        assert_eq!(input.stick_direction(), Some(Action::Left));
+
+        input.stick_x = 10_000;
+        input.stick_y = -20_000;
+        assert_eq!(input.stick_direction(), Some(Action::Up));
+
+        // A tie goes to the y axis
+        input.stick_x = 20_000;
+        input.stick_y = 20_000;
+        assert_eq!(input.stick_direction(), Some(Action::Down));
+    }
+
+    #[test]
+    fn stick_reports_direction_changes_only() {
+        let mut input = input();
+        let bindings = KeyBindings::default();
+
+        assert_eq!(input.translate(&stick(Axis::LeftX, 30_000), &bindings), Some(Action::Right));
+        assert_eq!(input.translate(&stick(Axis::LeftX, 25_000), &bindings), None);
+        assert_eq!(input.translate(&stick(Axis::LeftY, 32_000), &bindings), Some(Action::Down));
+        assert_eq!(input.translate(&stick(Axis::RightX, 32_000), &bindings), None);
+
+        // Back in the deadzone the repeat stops
+        assert_eq!(input.translate(&stick(Axis::LeftX, 0), &bindings), None);
+        assert_eq!(input.translate(&stick(Axis::LeftY, 0), &bindings), None);
+        assert!(input.held.is_none());
+    }
+}
@@ -0,0 +1,181 @@
+// Rust modules
+use std::collections::BTreeMap;
+
+// External modules
+use sdl2::keyboard::Keycode;
+use serde_derive::{Serialize, Deserialize};
+
+// Local modules
+use crate::input::{Action};
+
+// Keys that keep their meaning everywhere and can't be bound to an action
+const RESERVED_KEYS: [Keycode; 3] = [Keycode::Return, Keycode::KpEnter, Keycode::Escape];
+
+// Actions that can be bound to a key, Confirm and Back always use the reserved keys
+pub fn bindable_actions() -> [Action; 8] {
+    [Action::Up, Action::Down, Action::Left, Action::Right,
+     Action::Undo, Action::Redo, Action::Restart, Action::Pause]
+}
+
+pub fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::Up => "MOVE UP",
+        Action::Down => "MOVE DOWN",
+        Action::Left => "MOVE LEFT",
+        Action::Right => "MOVE RIGHT",
+        Action::Confirm => "CONFIRM",
+        Action::Back => "BACK",
+        Action::Undo => "UNDO",
+        Action::Redo => "REDO",
+        Action::Restart => "RESTART",
+        Action::Pause => "PAUSE",
+    }
+}
+
+fn default_key(action: Action) -> Keycode {
+    match action {
+        Action::Up => Keycode::Up,
+        Action::Down => Keycode::Down,
+        Action::Left => Keycode::Left,
+        Action::Right => Keycode::Right,
+        Action::Confirm => Keycode::Return,
+        Action::Back => Keycode::Escape,
+        Action::Undo => Keycode::Z,
+        Action::Redo => Keycode::Y,
+        Action::Restart => Keycode::R,
+        Action::Pause => Keycode::P,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindError {
+    // Return and Escape are needed to navigate
+    Reserved,
+    // The key is already bound to another action
+    Conflict(Action),
+}
+
+// The key for each game action. Stored with the SDL key names so the settings
+// file stays readable, unknown names and missing actions use the default key.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct KeyBindings {
+    keys: BTreeMap<Action, String>,
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> Keycode {
+        self.keys.get(&action)
+            .and_then(|name| Keycode::from_name(name))
+            .unwrap_or_else(|| default_key(action))
+    }
+
+    pub fn action_for(&self, key: Keycode) -> Option<Action> {
+        bindable_actions().iter().cloned().find(|action| self.get(*action) == key)
+    }
+
+    // Binds the key to the action, unless it's reserved or used by another action
+    pub fn set(&mut self, action: Action, key: Keycode) -> Result<(), BindError> {
+        if RESERVED_KEYS.contains(&key) {
+            return Err(BindError::Reserved)
+        }
+
+        match self.action_for(key) {
+            Some(other) if other != action => {
+                Err(BindError::Conflict(other))
+            },
+            _ => {
+                self.keys.insert(action, key.name());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.keys.clear();
+    }
+}
+
+// Upper case name of the key, the font has no lower case letters
+pub fn key_to_text(key: Keycode) -> String {
+    key.name().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{GameSettings};
+
+    #[test]
+    fn defaults() {
+        let bindings = KeyBindings::default();
+
+        for action in bindable_actions().iter() {
+            assert_eq!(bindings.get(*action), default_key(*action));
+            assert_eq!(bindings.action_for(default_key(*action)), Some(*action));
+        }
+        assert_eq!(bindings.action_for(Keycode::Q), None);
+    }
+
+    #[test]
+    fn bind_free_key() {
+        let mut bindings = KeyBindings::default();
+
+        assert_eq!(bindings.set(Action::Undo, Keycode::U), Ok(()));
+        assert_eq!(bindings.get(Action::Undo), Keycode::U);
+        assert_eq!(bindings.action_for(Keycode::Z), None);
+
+        // Binding the same key again is not a conflict
+        assert_eq!(bindings.set(Action::Undo, Keycode::U), Ok(()));
+    }
+
+    #[test]
+    fn key_of_another_action_is_a_conflict() {
+        let mut bindings = KeyBindings::default();
+
+        assert_eq!(bindings.set(Action::Undo, Keycode::Y), Err(BindError::Conflict(Action::Redo)));
+        assert_eq!(bindings.get(Action::Undo), Keycode::Z);
+        assert_eq!(bindings.get(Action::Redo), Keycode::Y);
+    }
+
+    #[test]
+    fn reserved_keys() {
+        let mut bindings = KeyBindings::default();
+
+        assert_eq!(bindings.set(Action::Pause, Keycode::Escape), Err(BindError::Reserved));
+        assert_eq!(bindings.set(Action::Undo, Keycode::Return), Err(BindError::Reserved));
+        assert_eq!(bindings.set(Action::Undo, Keycode::KpEnter), Err(BindError::Reserved));
+        assert_eq!(bindings.get(Action::Pause), Keycode::P);
+        assert_eq!(bindings.get(Action::Undo), Keycode::Z);
+    }
+
+    #[test]
+    fn reset_restores_defaults() {
+        let mut bindings = KeyBindings::default();
+
+        bindings.set(Action::Up, Keycode::W).unwrap();
+        bindings.set(Action::Restart, Keycode::Backspace).unwrap();
+        bindings.reset();
+
+        assert_eq!(bindings.get(Action::Up), Keycode::Up);
+        assert_eq!(bindings.get(Action::Restart), Keycode::R);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let mut settings = GameSettings::new();
+        settings.get_key_bindings_mut().set(Action::Left, Keycode::A).unwrap();
+
+        let data = serde_json::to_string(&settings).unwrap();
+        let loaded: GameSettings = serde_json::from_str(&data).unwrap();
+
+        assert_eq!(loaded.get_key_bindings().get(Action::Left), Keycode::A);
+        assert_eq!(loaded.get_key_bindings().get(Action::Right), Keycode::Right);
+    }
+
+    #[test]
+    fn unknown_key_name_uses_default() {
+        let bindings: KeyBindings = serde_json::from_str(r#"{ "keys": { "Undo": "NoSuchKey" } }"#).unwrap();
+
+        assert_eq!(bindings.get(Action::Undo), Keycode::Z);
+    }
+}
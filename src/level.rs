@@ -0,0 +1,537 @@
+// Rust modules
+use std::fs;
+use std::error;
+use std::fmt;
+use std::path::Path;
+use std::io::Error as StdIOError;
+
+// External modules
+use serde_derive::{Serialize, Deserialize};
+use serde_json::error::Error as JSONError;
+
+// Local modules
+use crate::board::{Board, Tile, Piece, Polarity, Direction};
+use crate::simulation;
+
+pub const LEVEL_VERSION: u32 = 1;
+
+// Level tile characters, used by both the JSON and the ASCII format:
+//
+// ' ' Floor        '#' Wall         '.' Goal
+// 'B' Block        '*' Block on goal
+// 'N' North magnet 'n' North magnet on goal
+// 'S' South magnet 's' South magnet on goal
+// '@' Player start '+' Player start on goal
+//
+// The ASCII format (*.txt) starts with optional header lines
+// beginning with ';' followed by the grid:
+//
+// ; version: 1
+// ; name: First steps
+// ; author: Willi Kappler
+// ##########
+// #B @   .N#
+// ##########
+//
+// In the JSON format the grid rows are stored in the "grid" array,
+// line numbers in error messages then refer to the row in that array.
+
+#[derive(Serialize, Deserialize, Debug)]
+struct LevelFile {
+    version: u32,
+    name: String,
+    author: String,
+    grid: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Level {
+    pub name: String,
+    pub author: String,
+    pub board: Board,
+}
+
+impl Level {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Level, LevelError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| LevelError::ReadError(e, path.display().to_string()))?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => {
+                Level::from_json(&data)
+            },
+            Some("txt") => {
+                Level::from_ascii(&data)
+            },
+            _ => {
+                Err(LevelError::UnknownFormat(path.display().to_string()))
+            }
+        }
+    }
+
+    pub fn from_json(data: &str) -> Result<Level, LevelError> {
+        let level_file: LevelFile = serde_json::from_str(data)?;
+
+        if level_file.version != LEVEL_VERSION {
+            return Err(LevelError::UnsupportedVersion(level_file.version))
+        }
+
+        Level::from_grid(level_file.name, level_file.author, &level_file.grid)
+    }
+
+    // Builds a level from grid rows with the same validation as for level files
+    pub fn from_grid(name: String, author: String, grid: &[String]) -> Result<Level, LevelError> {
+        let rows: Vec<(usize, &str)> = grid.iter()
+            .enumerate()
+            .map(|(i, row)| (i + 1, row.as_str()))
+            .collect();
+
+        Ok(Level {
+            name,
+            author,
+            board: parse_grid(&rows)?,
+        })
+    }
+
+    pub fn from_ascii(data: &str) -> Result<Level, LevelError> {
+        let mut version = None;
+        let mut name = String::new();
+        let mut author = String::new();
+        let mut rows = Vec::new();
+
+        for (i, line) in data.lines().enumerate() {
+            let line_number = i + 1;
+
+            if let Some(header) = line.strip_prefix(';') {
+                if !rows.is_empty() {
+                    return Err(LevelError::MisplacedHeader(line_number))
+                }
+
+                let header = header.trim();
+
+                if let Some(value) = header.strip_prefix("version:") {
+                    let value = value.trim();
+                    let parsed = value.parse::<u32>()
+                        .map_err(|_| LevelError::InvalidHeader(line_number, value.to_string()))?;
+                    version = Some(parsed);
+                } else if let Some(value) = header.strip_prefix("name:") {
+                    name = value.trim().to_string();
+                } else if let Some(value) = header.strip_prefix("author:") {
+                    author = value.trim().to_string();
+                }
+            } else if !(rows.is_empty() && line.trim().is_empty()) {
+                rows.push((line_number, line));
+            }
+        }
+
+        // Ignore empty lines at the end of the file
+        while let Some((_, row)) = rows.last() {
+            if row.trim().is_empty() {
+                rows.pop();
+            } else {
+                break
+            }
+        }
+
+        match version {
+            Some(LEVEL_VERSION) => {
+            },
+            Some(version) => {
+                return Err(LevelError::UnsupportedVersion(version))
+            },
+            None => {
+                return Err(LevelError::MissingVersion)
+            }
+        }
+
+        Ok(Level {
+            name,
+            author,
+            board: parse_grid(&rows)?,
+        })
+    }
+}
+
+// Levels are always saved in the JSON format. The grid rows are saved unchanged,
+// the level board can not be used because the magnets already acted on it.
+pub fn save_grid<T: AsRef<Path>>(path: T, name: &str, author: &str, grid: &[String]) -> Result<(), LevelError> {
+    let path = path.as_ref();
+    let level_file = LevelFile {
+        version: LEVEL_VERSION,
+        name: name.to_string(),
+        author: author.to_string(),
+        grid: grid.to_vec(),
+    };
+
+    let data = serde_json::to_string_pretty(&level_file)?;
+    fs::write(path, data)
+        .map_err(|e| LevelError::WriteError(e, path.display().to_string()))?;
+
+    Ok(())
+}
+
+// Inverse of the tile table above
+pub fn grid_char(tile: Tile, piece: Option<Piece>, player: bool) -> char {
+    match (tile, piece, player) {
+        (Tile::Wall, _, _) => '#',
+        (Tile::Floor, _, true) => '@',
+        (Tile::Goal, _, true) => '+',
+        (Tile::Floor, None, false) => ' ',
+        (Tile::Goal, None, false) => '.',
+        (Tile::Floor, Some(Piece::Block), false) => 'B',
+        (Tile::Goal, Some(Piece::Block), false) => '*',
+        (Tile::Floor, Some(Piece::Magnet(Polarity::North)), false) => 'N',
+        (Tile::Goal, Some(Piece::Magnet(Polarity::North)), false) => 'n',
+        (Tile::Floor, Some(Piece::Magnet(Polarity::South)), false) => 'S',
+        (Tile::Goal, Some(Piece::Magnet(Polarity::South)), false) => 's',
+    }
+}
+
+fn parse_grid(rows: &[(usize, &str)]) -> Result<Board, LevelError> {
+    let height = rows.len() as u32;
+    let width = rows.iter().map(|(_, row)| row.chars().count()).max().unwrap_or(0) as u32;
+
+    if width == 0 || height == 0 {
+        return Err(LevelError::EmptyGrid)
+    }
+
+    let mut board = Board::new(width, height);
+    let mut start = None;
+    let mut goals = Vec::new();
+    let mut num_of_blocks = 0;
+    let mut num_of_north = 0;
+    let mut num_of_south = 0;
+
+    for (y, (line_number, row)) in rows.iter().enumerate() {
+        let y = y as u32;
+        let mut x = 0;
+
+        for c in row.chars() {
+            let column = (x + 1) as usize;
+
+            let (tile, piece) = match c {
+                ' ' => (Tile::Floor, None),
+                '#' => (Tile::Wall, None),
+                '.' => (Tile::Goal, None),
+                'B' => (Tile::Floor, Some(Piece::Block)),
+                '*' => (Tile::Goal, Some(Piece::Block)),
+                'N' => (Tile::Floor, Some(Piece::Magnet(Polarity::North))),
+                'n' => (Tile::Goal, Some(Piece::Magnet(Polarity::North))),
+                'S' => (Tile::Floor, Some(Piece::Magnet(Polarity::South))),
+                's' => (Tile::Goal, Some(Piece::Magnet(Polarity::South))),
+                '@' => (Tile::Floor, None),
+                '+' => (Tile::Goal, None),
+                _ => {
+                    return Err(LevelError::UnknownTile(*line_number, column, c))
+                }
+            };
+
+            if c == '@' || c == '+' {
+                if start.is_some() {
+                    return Err(LevelError::MultipleStarts(*line_number, column))
+                }
+                start = Some((x, y));
+            }
+
+            if tile == Tile::Goal {
+                goals.push((x, y, *line_number, column));
+            }
+
+            match piece {
+                Some(Piece::Block) => num_of_blocks += 1,
+                Some(Piece::Magnet(Polarity::North)) => num_of_north += 1,
+                Some(Piece::Magnet(Polarity::South)) => num_of_south += 1,
+                None => {},
+            }
+
+            board.set_tile((x, y), tile);
+            board.set_piece((x, y), piece);
+            x += 1;
+        }
+
+        // Short rows are filled up with walls
+        while x < width {
+            board.set_tile((x, y), Tile::Wall);
+            x += 1;
+        }
+    }
+
+    let (start_x, start_y) = match start {
+        Some(start) => start,
+        None => return Err(LevelError::MissingStart),
+    };
+
+    if goals.is_empty() {
+        return Err(LevelError::MissingGoal)
+    }
+
+    // Two opposite magnets can merge into an additional block
+    let possible_blocks = num_of_blocks + num_of_north.min(num_of_south);
+
+    if possible_blocks < goals.len() {
+        return Err(LevelError::NotEnoughBlocks(goals.len(), possible_blocks))
+    }
+
+    board.set_player((start_x, start_y));
+
+    let reachable = reachable_tiles(&board, start_x, start_y);
+
+    for (x, y, line_number, column) in goals {
+        if !reachable[(y * width + x) as usize] {
+            return Err(LevelError::UnreachableGoal(line_number, column))
+        }
+    }
+
+    simulation::apply_magnets(&mut board);
+
+    Ok(board)
+}
+
+// Flood fill from the player start position, only walls block the way.
+fn reachable_tiles(board: &Board, start_x: u32, start_y: u32) -> Vec<bool> {
+    let width = board.get_width();
+    let height = board.get_height();
+    let mut reachable = vec![false; (width * height) as usize];
+    let mut stack = vec![(start_x, start_y)];
+
+    while let Some((x, y)) = stack.pop() {
+        let index = (y * width + x) as usize;
+
+        if reachable[index] || board.get_tile((x, y)) == Tile::Wall {
+            continue
+        }
+
+        reachable[index] = true;
+
+        for direction in Direction::all().iter() {
+            if let Some(next) = board.neighbour((x, y), *direction) {
+                stack.push(next);
+            }
+        }
+    }
+
+    reachable
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    ReadError(StdIOError, String),
+    WriteError(StdIOError, String),
+    ParseError(JSONError),
+    UnknownFormat(String),
+    MissingVersion,
+    UnsupportedVersion(u32),
+    MisplacedHeader(usize),
+    InvalidHeader(usize, String),
+    EmptyGrid,
+    UnknownTile(usize, usize, char),
+    MultipleStarts(usize, usize),
+    MissingStart,
+    MissingGoal,
+    NotEnoughBlocks(usize, usize),
+    UnreachableGoal(usize, usize),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LevelError::ReadError(ref e, ref path) => {
+                write!(f, "IO error while reading the level file: '{}', {}", path, e)
+            },
+            LevelError::WriteError(ref e, ref path) => {
+                write!(f, "IO error while writing the level file: '{}', {}", path, e)
+            },
+            LevelError::ParseError(ref e) => {
+                write!(f, "Parse error while reading the level file: {}", e)
+            },
+            LevelError::UnknownFormat(ref path) => {
+                write!(f, "Unknown level file format (expected .json or .txt): '{}'", path)
+            },
+            LevelError::MissingVersion => {
+                write!(f, "Level file has no version header")
+            },
+            LevelError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported level version: {}, expected: {}", version, LEVEL_VERSION)
+            },
+            LevelError::MisplacedHeader(line) => {
+                write!(f, "Header line after the level grid at line {}", line)
+            },
+            LevelError::InvalidHeader(line, ref value) => {
+                write!(f, "Invalid header value '{}' at line {}", value, line)
+            },
+            LevelError::EmptyGrid => {
+                write!(f, "Level grid is empty")
+            },
+            LevelError::UnknownTile(line, column, c) => {
+                write!(f, "Unknown tile '{}' at line {}, column {}", c, line, column)
+            },
+            LevelError::MultipleStarts(line, column) => {
+                write!(f, "Second player start at line {}, column {}", line, column)
+            },
+            LevelError::MissingStart => {
+                write!(f, "Level has no player start ('@' or '+')")
+            },
+            LevelError::MissingGoal => {
+                write!(f, "Level has no goal ('.', '*', 'n', 's' or '+')")
+            },
+            LevelError::NotEnoughBlocks(goals, blocks) => {
+                write!(f, "Level has {} goals but only {} blocks (including merged magnets)", goals, blocks)
+            },
+            LevelError::UnreachableGoal(line, column) => {
+                write!(f, "Goal at line {}, column {} can not be reached from the start", line, column)
+            },
+        }
+    }
+}
+
+impl error::Error for LevelError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            LevelError::ReadError(ref e, _) => {
+                Some(e)
+            },
+            LevelError::WriteError(ref e, _) => {
+                Some(e)
+            },
+            LevelError::ParseError(ref e) => {
+                Some(e)
+            },
+            _ => {
+                None
+            },
+        }
+    }
+}
+
+impl From<JSONError> for LevelError {
+    fn from(e: JSONError) -> LevelError {
+        LevelError::ParseError(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ascii(grid: &str) -> Result<Level, LevelError> {
+        Level::from_ascii(&format!("; version: 1\n{}", grid))
+    }
+
+    #[test]
+    fn ascii_headers() {
+        let level = Level::from_ascii("\n; version: 1\n; name: First steps\n; author: Someone\n#####\n#@B.#\n#####\n\n").unwrap();
+
+        assert_eq!(level.name, "First steps");
+        assert_eq!(level.author, "Someone");
+        assert_eq!(level.board.get_width(), 5);
+        assert_eq!(level.board.get_height(), 3);
+        assert_eq!(level.board.get_player(), (1, 1));
+        assert_eq!(level.board.get_piece((2, 1)), Some(Piece::Block));
+        assert_eq!(level.board.get_tile((3, 1)), Tile::Goal);
+    }
+
+    #[test]
+    fn ascii_header_errors() {
+        assert!(matches!(Level::from_ascii("#@B.#\n"), Err(LevelError::MissingVersion)));
+        assert!(matches!(Level::from_ascii("; version: 2\n#@B.#\n"), Err(LevelError::UnsupportedVersion(2))));
+        assert!(matches!(Level::from_ascii("; version: one\n#@B.#\n"), Err(LevelError::InvalidHeader(1, _))));
+        assert!(matches!(ascii("#@B.#\n; name: late\n"), Err(LevelError::MisplacedHeader(3))));
+    }
+
+    #[test]
+    fn json_level() {
+        let level = Level::from_json("{ \"version\": 1, \"name\": \"A\", \"author\": \"B\", \"grid\": [\"#####\", \"#@B.#\", \"#####\"] }").unwrap();
+
+        assert_eq!(level.name, "A");
+        assert_eq!(level.author, "B");
+        assert_eq!(level.board.get_player(), (1, 1));
+    }
+
+    #[test]
+    fn json_errors() {
+        assert!(matches!(Level::from_json("{ \"version\": 1 }"), Err(LevelError::ParseError(_))));
+        assert!(matches!(Level::from_json(r#"{ "version": 3, "name": "", "author": "", "grid": [] }"#),
+            Err(LevelError::UnsupportedVersion(3))));
+        // Line numbers refer to the row in the grid array
+        assert!(matches!(Level::from_json("{ \"version\": 1, \"name\": \"\", \"author\": \"\", \"grid\": [\"#@B.#\", \"#X\"] }"),
+            Err(LevelError::UnknownTile(2, 2, 'X'))));
+    }
+
+    #[test]
+    fn load_errors() {
+        assert!(matches!(Level::load("assets/levels/missing.json"), Err(LevelError::ReadError(_, _))));
+        assert!(matches!(Level::load("Cargo.toml"), Err(LevelError::UnknownFormat(_))));
+    }
+
+    #[test]
+    fn short_rows_are_padded_with_walls() {
+        let level = ascii("#####\n#@B.#\n##\n").unwrap();
+        let board = &level.board;
+
+        assert_eq!(board.get_width(), 5);
+        assert_eq!(board.get_tile((1, 2)), Tile::Wall);
+        assert_eq!(board.get_tile((2, 2)), Tile::Wall);
+        assert_eq!(board.get_tile((4, 2)), Tile::Wall);
+    }
+
+    #[test]
+    fn grid_errors() {
+        assert!(matches!(ascii(""), Err(LevelError::EmptyGrid)));
+        assert!(matches!(ascii("#@B.#\n#?\n"), Err(LevelError::UnknownTile(3, 2, '?'))));
+        assert!(matches!(ascii("#@B.@#\n"), Err(LevelError::MultipleStarts(2, 5))));
+        assert!(matches!(ascii("# B.#\n"), Err(LevelError::MissingStart)));
+        assert!(matches!(ascii("#@B #\n"), Err(LevelError::MissingGoal)));
+    }
+
+    #[test]
+    fn not_enough_blocks() {
+        assert!(matches!(ascii("#@B..#\n"), Err(LevelError::NotEnoughBlocks(2, 1))));
+        // Two magnets of the same polarity never merge
+        assert!(matches!(ascii("#@N#N.#\n"), Err(LevelError::NotEnoughBlocks(1, 0))));
+        // Opposite magnets count as one block
+        assert!(ascii("#@N S.#\n").is_ok());
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        assert!(matches!(ascii("#####\n#@B##\n###.#\n#####\n"), Err(LevelError::UnreachableGoal(4, 4))));
+    }
+
+    #[test]
+    fn magnets_settle_on_load() {
+        let level = ascii("#@ .N  B#\n").unwrap();
+
+        assert_eq!(level.board.get_piece((7, 0)), None);
+        assert_eq!(level.board.get_piece((5, 0)), Some(Piece::Block));
+    }
+
+    #[test]
+    fn saved_grid_is_unchanged() {
+        // The magnet would pull the block on load
+        let grid = vec!["#@ .N  B#".to_string()];
+        let path = std::env::temp_dir().join("mini_magnets_saved_grid.json");
+
+        save_grid(&path, "A", "B", &grid).unwrap();
+        let data = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let level_file: LevelFile = serde_json::from_str(&data).unwrap();
+        assert_eq!(level_file.grid, grid);
+        assert_eq!(level_file.name, "A");
+        assert_eq!(level_file.author, "B");
+    }
+
+    #[test]
+    fn grid_chars_round_trip() {
+        let grid = vec!["#######".to_string(), "#+B*B.#".to_string(), "#######".to_string()];
+        let board = Level::from_grid(String::new(), String::new(), &grid).unwrap().board;
+        let chars: Vec<String> = (0..board.get_height()).map(|y| {
+            (0..board.get_width()).map(|x| {
+                grid_char(board.get_tile((x, y)), board.get_piece((x, y)), board.get_player() == (x, y))
+            }).collect()
+        }).collect();
+
+        assert_eq!(chars, grid);
+    }
+}
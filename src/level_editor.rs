@@ -0,0 +1,929 @@
+// Rust modules
+use std::rc::Rc;
+use std::path::Path;
+
+// External modules
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::board::{Board, Tile, Piece, Polarity, Direction, Position};
+use crate::board_view::{BoardView};
+use crate::level::{self, Level, LevelError};
+use crate::simulation;
+use crate::solver::{self, SolverResult};
+use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
+use crate::text_fx::{Font, StaticText};
+use crate::audio::{Playlist};
+use crate::input::{Action};
+use crate::key_bindings::{KeyBindings, key_to_text};
+
+const CELL_SIZE: u32 = 28;
+const BOARD_X: i32 = 10;
+const BOARD_Y: i32 = 60;
+const MIN_SIZE: u32 = 4;
+const MAX_SIZE: u32 = 15;
+const MAX_TEXT_LENGTH: usize = 20;
+// Characters of the 24 pixel font that fit into one status line
+const STATUS_LENGTH: usize = 32;
+// Keep the editor responsive, the --verify mode uses a higher limit
+const SOLVER_STATES: usize = 200_000;
+const LEVEL_DIRECTORY: &str = "assets/levels/";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tool {
+    Floor,
+    Wall,
+    Goal,
+    Block,
+    NorthMagnet,
+    SouthMagnet,
+    Player,
+}
+
+impl Tool {
+    fn all() -> [Tool; 7] {
+        [Tool::Floor, Tool::Wall, Tool::Goal, Tool::Block, Tool::NorthMagnet, Tool::SouthMagnet, Tool::Player]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Floor => "FLOOR",
+            Tool::Wall => "WALL",
+            Tool::Goal => "GOAL",
+            Tool::Block => "BLOCK",
+            Tool::NorthMagnet => "NORTH",
+            Tool::SouthMagnet => "SOUTH",
+            Tool::Player => "PLAYER",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Tool::all().iter().position(|t| t == self).unwrap()
+    }
+
+    fn next(&self) -> Tool {
+        let tools = Tool::all();
+        tools[(self.index() + 1) % tools.len()]
+    }
+
+    fn prev(&self) -> Tool {
+        let tools = Tool::all();
+        tools[(self.index() + tools.len() - 1) % tools.len()]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Focus {
+    Board,
+    Panel,
+    EditName,
+    EditAuthor,
+}
+
+// Playing the edited level without leaving the editor
+struct TestPlay {
+    board: Board,
+    start_board: Board,
+    board_view: BoardView,
+    moves: u32,
+    solved: bool,
+    // Shown again after a restart
+    help: String,
+}
+
+pub struct LevelEditor {
+    panel: BaseMenu,
+    board: Board,
+    // The editor allows a level without player, this is reported when validating
+    player: Option<Position>,
+    board_view: BoardView,
+    cursor: Position,
+    tool: Tool,
+    focus: Focus,
+    name: String,
+    author: String,
+    test_play: Option<TestPlay>,
+    // File that SAVE replaces without asking again
+    overwrite: Option<String>,
+    name_text: StaticText,
+    author_text: StaticText,
+    // Long messages are wrapped over both lines
+    status: [StaticText; 2],
+}
+
+impl LevelEditor {
+    pub fn new() -> LevelEditor {
+        let mut panel = BaseMenu::new(660, 10, 30, "EDITOR".to_string(), Vec::new(), vec![
+            "TOOL:".to_string(),
+            "WIDTH:".to_string(),
+            "HEIGHT:".to_string(),
+            "NAME".to_string(),
+            "AUTHOR".to_string(),
+            "SOLVE".to_string(),
+            "TEST".to_string(),
+            "SAVE".to_string(),
+            "BACK".to_string(),
+        ]);
+        panel.set_value_items(vec![0, 1, 2]);
+
+        LevelEditor {
+            panel,
+            board: empty_board(10, 8),
+            player: Some((1, 1)),
+            board_view: BoardView::new(CELL_SIZE),
+            cursor: (1, 1),
+            tool: Tool::Wall,
+            focus: Focus::Board,
+            name: String::new(),
+            author: String::new(),
+            test_play: None,
+            overwrite: None,
+            name_text: StaticText::new(10, 485, String::new()),
+            author_text: StaticText::new(10, 515, String::new()),
+            status: [StaticText::new(10, 545, String::new()), StaticText::new(10, 575, String::new())],
+        }
+    }
+
+    fn process_test_play(&mut self, event: &Event, action: Option<Action>) {
+        // T only stops the test play if it's not bound to an action
+        if let (Event::KeyDown { keycode: Some(Keycode::T), .. }, None) = (event, action) {
+            self.stop_test_play();
+            return
+        }
+
+        let direction = match action {
+            Some(Action::Back) => {
+                self.stop_test_play();
+                return
+            },
+            Some(Action::Restart) => {
+                let mut help = String::new();
+
+                if let Some(test_play) = &mut self.test_play {
+                    test_play.board = test_play.start_board.clone();
+                    test_play.board_view.stop_animation();
+                    test_play.moves = 0;
+                    test_play.solved = false;
+                    help = test_play.help.clone();
+                }
+                self.set_status(&help);
+                return
+            },
+            Some(Action::Left) => Direction::Left,
+            Some(Action::Right) => Direction::Right,
+            Some(Action::Up) => Direction::Up,
+            Some(Action::Down) => Direction::Down,
+            _ => return,
+        };
+
+        let mut status = None;
+
+        if let Some(test_play) = &mut self.test_play {
+            if test_play.solved {
+                return
+            }
+
+            let result = simulation::apply_move(&test_play.board, direction);
+
+            if result.has_moved() {
+                test_play.board_view.start_animation(&result.events);
+                test_play.board = result.board;
+                test_play.moves += 1;
+                test_play.solved = test_play.board.is_solved();
+
+                if test_play.solved {
+                    status = Some(format!("SOLVED IN {} MOVES!", test_play.moves));
+                }
+            }
+        }
+
+        if let Some(status) = status {
+            self.set_status(&status);
+        }
+    }
+
+    fn process_text(&mut self, event: &Event) {
+        let text = match self.focus {
+            Focus::EditName => &mut self.name,
+            _ => &mut self.author,
+        };
+
+        match event {
+            Event::TextInput { text: input, .. } => {
+                for c in input.to_uppercase().chars() {
+                    // Only characters available in the bitmap font
+                    if (' '..='_').contains(&c) && text.len() < MAX_TEXT_LENGTH {
+                        text.push(c);
+                    }
+                }
+            },
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => {
+                text.pop();
+            },
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } |
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                self.focus = Focus::Panel;
+            },
+            _ => {
+            }
+        }
+    }
+
+    // Returns true if the event was a mouse event on the board
+    fn process_mouse(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MouseButtonDown { mouse_btn, x, y, .. } => {
+                match self.board_view.cell_at(&self.board, BOARD_X, BOARD_Y, x, y) {
+                    Some(pos) => {
+                        self.focus = Focus::Board;
+                        self.cursor = pos;
+
+                        match mouse_btn {
+                            MouseButton::Left => self.place(pos),
+                            MouseButton::Right => self.erase(pos),
+                            _ => {},
+                        }
+
+                        true
+                    },
+                    None => false,
+                }
+            },
+            Event::MouseMotion { mousestate, x, y, .. } => {
+                match self.board_view.cell_at(&self.board, BOARD_X, BOARD_Y, x, y) {
+                    Some(pos) if self.focus == Focus::Board => {
+                        self.cursor = pos;
+
+                        // Paint while a button is held down
+                        if mousestate.left() {
+                            self.place(pos);
+                        } else if mousestate.right() {
+                            self.erase(pos);
+                        }
+
+                        true
+                    },
+                    _ => false,
+                }
+            },
+            _ => false,
+        }
+    }
+
+    // Clicking a panel entry moves the focus to the panel
+    fn is_panel_click(&self, event: &Event) -> bool {
+        match *event {
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => self.panel.item_at(x, y).is_some(),
+            _ => false,
+        }
+    }
+
+    // Editor shortcuts, returns true if the key was one of them.
+    // Keys that are bound to an action are left to the action.
+    fn process_board_keys(&mut self, event: &Event, action: Option<Action>, bindings: &KeyBindings) -> bool {
+        let keycode = match (event, action) {
+            (Event::KeyDown { keycode: Some(keycode), .. }, None) => *keycode,
+            _ => {
+                return false
+            }
+        };
+
+        // Number keys select the tool directly
+        let tool_keys = [Keycode::Num1, Keycode::Num2, Keycode::Num3, Keycode::Num4,
+            Keycode::Num5, Keycode::Num6, Keycode::Num7];
+
+        match keycode {
+            Keycode::Space => {
+                self.place(self.cursor);
+            },
+            Keycode::Delete | Keycode::Backspace => {
+                self.erase(self.cursor);
+            },
+            Keycode::Tab => {
+                self.focus = Focus::Panel;
+            },
+            Keycode::T => {
+                self.start_test_play(bindings);
+            },
+            _ => {
+                match tool_keys.iter().position(|k| *k == keycode) {
+                    Some(index) => {
+                        self.tool = Tool::all()[index];
+                        self.update_panel();
+                    },
+                    None => {
+                        return false
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn process_board(&mut self, action: Action) {
+        match action {
+            Action::Left => {
+                self.move_cursor(Direction::Left);
+            },
+            Action::Right => {
+                self.move_cursor(Direction::Right);
+            },
+            Action::Up => {
+                self.move_cursor(Direction::Up);
+            },
+            Action::Down => {
+                self.move_cursor(Direction::Down);
+            },
+            Action::Confirm => {
+                self.place(self.cursor);
+            },
+            Action::Back => {
+                self.focus = Focus::Panel;
+            },
+            _ => {
+            }
+        }
+    }
+
+    fn process_panel(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let game_screen = &mut *context.screen;
+        let audio = &*context.audio;
+        let headless = context.headless;
+
+        if let (Event::KeyDown { keycode: Some(Keycode::Tab), .. }, None) = (event, action) {
+            self.focus = Focus::Board;
+            return
+        }
+
+        let action = match self.panel.process(event, action, audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        // Anything but SAVE cancels replacing an existing file
+        if action != Action::Confirm || self.panel.get_selected() != 7 {
+            self.overwrite = None;
+        }
+
+        match action {
+            Action::Back => {
+                self.panel.back(audio);
+                game_screen.back();
+            },
+            Action::Confirm => {
+                match self.panel.get_selected() {
+                    0..=2 => {
+                        self.focus = Focus::Board;
+                    },
+                    3 => {
+                        self.focus = Focus::EditName;
+                    },
+                    4 => {
+                        self.focus = Focus::EditAuthor;
+                    },
+                    5 => {
+                        self.solve();
+                    },
+                    6 => {
+                        self.start_test_play(context.settings.get_key_bindings());
+                    },
+                    7 => {
+                        self.save(headless);
+                    },
+                    8 => {
+                        game_screen.back();
+                    },
+                    _ => {
+                        unreachable!();
+                    }
+                }
+            },
+            Action::Left => {
+                let changed = self.change_value(-1);
+                self.panel.value_changed(audio, changed);
+            },
+            Action::Right => {
+                let changed = self.change_value(1);
+                self.panel.value_changed(audio, changed);
+            },
+            _ => {
+            }
+        }
+    }
+
+    // True if the tool or the board size has changed
+    fn change_value(&mut self, delta: i32) -> bool {
+        let width = self.board.get_width();
+        let height = self.board.get_height();
+        let tool = self.tool;
+
+        match self.panel.get_selected() {
+            0 => {
+                self.tool = if delta > 0 { self.tool.next() } else { self.tool.prev() };
+            },
+            1 => {
+                self.resize(change_size(width, delta), height);
+            },
+            2 => {
+                self.resize(width, change_size(height, delta));
+            },
+            _ => {
+                return false
+            }
+        }
+
+        self.update_panel();
+        tool != self.tool || width != self.board.get_width() || height != self.board.get_height()
+    }
+
+    fn update_panel(&mut self) {
+        self.panel.change_menu(0, format!("TOOL: {}", self.tool.name()));
+        self.panel.change_menu(1, format!("WIDTH: {}", self.board.get_width()));
+        self.panel.change_menu(2, format!("HEIGHT: {}", self.board.get_height()));
+    }
+
+    fn move_cursor(&mut self, direction: Direction) {
+        if let Some(pos) = self.board.neighbour(self.cursor, direction) {
+            self.cursor = pos;
+        }
+    }
+
+    fn place(&mut self, pos: Position) {
+        match self.tool {
+            Tool::Floor => {
+                self.board.set_tile(pos, Tile::Floor);
+            },
+            Tool::Wall => {
+                self.erase(pos);
+                self.board.set_tile(pos, Tile::Wall);
+            },
+            Tool::Goal => {
+                self.board.set_tile(pos, Tile::Goal);
+            },
+            Tool::Block => {
+                self.place_piece(pos, Piece::Block);
+            },
+            Tool::NorthMagnet => {
+                self.place_piece(pos, Piece::Magnet(Polarity::North));
+            },
+            Tool::SouthMagnet => {
+                self.place_piece(pos, Piece::Magnet(Polarity::South));
+            },
+            Tool::Player => {
+                self.clear_wall(pos);
+                self.board.set_piece(pos, None);
+                self.player = Some(pos);
+            },
+        }
+    }
+
+    fn place_piece(&mut self, pos: Position, piece: Piece) {
+        self.clear_wall(pos);
+        self.board.set_piece(pos, Some(piece));
+
+        if self.player == Some(pos) {
+            self.player = None;
+        }
+    }
+
+    fn clear_wall(&mut self, pos: Position) {
+        if self.board.get_tile(pos) == Tile::Wall {
+            self.board.set_tile(pos, Tile::Floor);
+        }
+    }
+
+    fn erase(&mut self, pos: Position) {
+        self.board.set_tile(pos, Tile::Floor);
+        self.board.set_piece(pos, None);
+
+        if self.player == Some(pos) {
+            self.player = None;
+        }
+    }
+
+    // Keeps everything that still fits into the new size
+    fn resize(&mut self, width: u32, height: u32) {
+        let mut board = Board::new(width, height);
+
+        for y in 0..height.min(self.board.get_height()) {
+            for x in 0..width.min(self.board.get_width()) {
+                board.set_tile((x, y), self.board.get_tile((x, y)));
+                board.set_piece((x, y), self.board.get_piece((x, y)));
+            }
+        }
+
+        if let Some((x, y)) = self.player {
+            if x >= width || y >= height {
+                self.player = None;
+            }
+        }
+
+        self.cursor = (self.cursor.0.min(width - 1), self.cursor.1.min(height - 1));
+        self.board = board;
+    }
+
+    fn grid(&self) -> Vec<String> {
+        (0..self.board.get_height()).map(|y| {
+            (0..self.board.get_width()).map(|x| {
+                let pos = (x, y);
+                level::grid_char(self.board.get_tile(pos), self.board.get_piece(pos), self.player == Some(pos))
+            }).collect()
+        }).collect()
+    }
+
+    fn validate(&mut self) -> Option<Level> {
+        match Level::from_grid(self.name.clone(), self.author.clone(), &self.grid()) {
+            Ok(level) => {
+                Some(level)
+            },
+            Err(e) => {
+                self.set_error(&e);
+                None
+            }
+        }
+    }
+
+    fn solve(&mut self) {
+        if let Some(level) = self.validate() {
+            match solver::solve(&level.board, SOLVER_STATES) {
+                SolverResult::Solved(solution) => {
+                    self.set_status(&format!("MIN MOVES: {}", solution.len()));
+                },
+                SolverResult::Unsolvable(_) => {
+                    self.set_status("LEVEL IS UNSOLVABLE!");
+                },
+                SolverResult::LimitReached(_) => {
+                    self.set_status("TOO COMPLEX FOR THE SOLVER");
+                }
+            }
+        }
+    }
+
+    fn start_test_play(&mut self, bindings: &KeyBindings) {
+        if let Some(level) = self.validate() {
+            let help = format!("TEST PLAY\nESC: BACK, {}: RESTART", key_to_text(bindings.get(Action::Restart)));
+
+            self.set_status(&help);
+            self.test_play = Some(TestPlay {
+                board: level.board.clone(),
+                start_board: level.board,
+                board_view: BoardView::new(CELL_SIZE),
+                moves: 0,
+                solved: false,
+                help,
+            });
+        }
+    }
+
+    fn stop_test_play(&mut self) {
+        self.test_play = None;
+        self.set_status("");
+    }
+
+    fn save(&mut self, headless: bool) {
+        if self.name.trim().is_empty() {
+            self.set_status("ENTER A NAME FIRST");
+            return
+        }
+
+        // Only checked, the magnets would already have moved in the validated level
+        if self.validate().is_some() {
+            let file_name = level_file_name(&self.name);
+            let path = format!("{}{}", LEVEL_DIRECTORY, file_name);
+
+            // Existing levels, e.g. the ones of the level pack, are only replaced after a second SAVE
+            if Path::new(&path).exists() && self.overwrite.as_ref() != Some(&path) {
+                self.set_status(&format!("{} EXISTS, SAVE AGAIN TO REPLACE", file_name.to_uppercase()));
+                self.overwrite = Some(path);
+                return
+            }
+
+            self.overwrite = None;
+
+            if headless {
+                self.set_status("NOT SAVED IN HEADLESS MODE");
+                return
+            }
+
+            match level::save_grid(&path, &self.name, &self.author, &self.grid()) {
+                Ok(_) => {
+                    self.set_status(&format!("SAVED: {}", file_name.to_uppercase()));
+                },
+                Err(e) => {
+                    self.set_error(&e);
+                }
+            }
+        }
+    }
+
+    fn set_error(&mut self, error: &LevelError) {
+        println!("Level editor: {}", error);
+        self.set_status(&error.to_string().to_uppercase());
+    }
+
+    fn set_status(&mut self, text: &str) {
+        let (first, second) = wrap_status(text);
+        self.status[0].set_text(first);
+        self.status[1].set_text(second);
+    }
+}
+
+impl Screen for LevelEditor {
+    // Needs the raw events for typing, mouse painting and shortcuts
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        if self.test_play.is_some() {
+            self.process_test_play(event, action);
+            return
+        }
+
+        if self.focus != Focus::Panel {
+            self.overwrite = None;
+        }
+
+        match self.focus {
+            Focus::EditName | Focus::EditAuthor => {
+                self.process_text(event);
+            },
+            Focus::Board if self.is_panel_click(event) => {
+                self.focus = Focus::Panel;
+                self.process_panel(event, action, context);
+            },
+            Focus::Board => {
+                if !self.process_mouse(event) && !self.process_board_keys(event, action, context.settings.get_key_bindings()) {
+                    if let Some(action) = action {
+                        self.process_board(action);
+                    }
+                }
+            },
+            Focus::Panel => {
+                if !self.process_mouse(event) {
+                    self.process_panel(event, action, context);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, _context: &mut ScreenContext) {
+        self.panel.update();
+
+        if let Some(test_play) = &mut self.test_play {
+            test_play.board_view.update();
+        }
+
+        let current = self.focus;
+        let cursor = |focus| if current == focus { "_" } else { "" };
+        self.name_text.set_text(format!("NAME: {}{}", self.name, cursor(Focus::EditName)));
+        self.author_text.set_text(format!("AUTHOR: {}{}", self.author, cursor(Focus::EditAuthor)));
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.panel.draw(canvas, alpha);
+
+        match &self.test_play {
+            Some(test_play) => {
+                test_play.board_view.draw(canvas, &test_play.board, BOARD_X, BOARD_Y, alpha);
+            },
+            None => {
+                self.board_view.draw_level(canvas, &self.board, BOARD_X, BOARD_Y, alpha);
+
+                if let Some(player) = self.player {
+                    self.board_view.draw_player(canvas, player, BOARD_X, BOARD_Y, alpha);
+                }
+
+                if self.focus == Focus::Board {
+                    self.board_view.draw_cursor(canvas, self.cursor, BOARD_X, BOARD_Y);
+                }
+            }
+        }
+
+        self.name_text.draw(canvas);
+        self.author_text.draw(canvas);
+        for status in self.status.iter() {
+            status.draw(canvas);
+        }
+    }
+
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.panel.get_selected())
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.panel.set_font(font);
+        self.name_text.set_font(font);
+        self.author_text.set_font(font);
+        for status in self.status.iter_mut() {
+            status.set_font(font);
+        }
+        self.update_panel();
+    }
+
+    fn playlist(&self) -> Playlist {
+        Playlist::new(&["editor"], false)
+    }
+}
+
+fn change_size(size: u32, delta: i32) -> u32 {
+    ((size as i32) + delta).max(MIN_SIZE as i32).min(MAX_SIZE as i32) as u32
+}
+
+// Lower case letters and digits of the level name, everything else becomes '_'
+fn level_file_name(name: &str) -> String {
+    format!("{}.json", name.trim().to_lowercase().replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+}
+
+// A line break in the text or the last space that fits into the first line
+// splits the text, the rest is cut off. Errors are printed on the console in full.
+fn wrap_status(text: &str) -> (String, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let split = match chars.iter().position(|c| *c == '\n') {
+        Some(index) => index,
+        None if chars.len() <= STATUS_LENGTH => chars.len(),
+        None => chars[..=STATUS_LENGTH].iter().rposition(|c| *c == ' ').filter(|i| *i > 0).unwrap_or(STATUS_LENGTH),
+    };
+    let line = |part: &[char]| part.iter().collect::<String>().trim().chars().take(STATUS_LENGTH).collect();
+
+    (line(&chars[..split]), line(&chars[split..]))
+}
+
+// Board surrounded by walls
+fn empty_board(width: u32, height: u32) -> Board {
+    let mut board = Board::new(width, height);
+
+    for x in 0..width {
+        board.set_tile((x, 0), Tile::Wall);
+        board.set_tile((x, height - 1), Tile::Wall);
+    }
+
+    for y in 0..height {
+        board.set_tile((0, y), Tile::Wall);
+        board.set_tile((width - 1, y), Tile::Wall);
+    }
+
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard::Mod;
+
+    fn key(keycode: Keycode) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None,
+            keymod: Mod::NOMOD, repeat: false }
+    }
+
+    // A valid level: the empty board with a goal and a block
+    fn editor() -> LevelEditor {
+        let mut editor = LevelEditor::new();
+
+        editor.tool = Tool::Goal;
+        editor.place((3, 3));
+        editor.tool = Tool::Block;
+        editor.place((5, 5));
+        editor
+    }
+
+    #[test]
+    fn place_tools() {
+        let mut editor = LevelEditor::new();
+        let pos = (4, 4);
+
+        editor.tool = Tool::Goal;
+        editor.place(pos);
+        editor.tool = Tool::NorthMagnet;
+        editor.place(pos);
+        assert_eq!(editor.board.get_tile(pos), Tile::Goal);
+        assert_eq!(editor.board.get_piece(pos), Some(Piece::Magnet(Polarity::North)));
+
+        // A wall replaces everything
+        editor.tool = Tool::Wall;
+        editor.place(pos);
+        assert_eq!(editor.board.get_tile(pos), Tile::Wall);
+        assert_eq!(editor.board.get_piece(pos), None);
+
+        // The player removes the wall and moves there
+        editor.tool = Tool::Player;
+        editor.place(pos);
+        assert_eq!(editor.board.get_tile(pos), Tile::Floor);
+        assert_eq!(editor.player, Some(pos));
+
+        // A piece replaces the player
+        editor.tool = Tool::Block;
+        editor.place(pos);
+        assert_eq!(editor.board.get_piece(pos), Some(Piece::Block));
+        assert_eq!(editor.player, None);
+    }
+
+    #[test]
+    fn erase_clears_the_cell() {
+        let mut editor = editor();
+
+        editor.erase((3, 3));
+        editor.erase((5, 5));
+        editor.erase((1, 1));
+        editor.erase((0, 0));
+
+        for pos in [(3, 3), (5, 5), (1, 1), (0, 0)].iter() {
+            assert_eq!(editor.board.get_tile(*pos), Tile::Floor);
+            assert_eq!(editor.board.get_piece(*pos), None);
+        }
+        assert_eq!(editor.player, None);
+    }
+
+    #[test]
+    fn file_name_from_level_name() {
+        assert_eq!(level_file_name("FIRST STEPS"), "first_steps.json");
+        assert_eq!(level_file_name("  LEVEL 5! "), "level_5_.json");
+    }
+
+    #[test]
+    fn save_needs_a_name() {
+        let mut editor = editor();
+
+        editor.name = "   ".to_string();
+        editor.save(true);
+        assert_eq!(editor.overwrite, None);
+    }
+
+    #[test]
+    fn save_again_to_replace() {
+        let mut editor = editor();
+        let path = format!("{}level01.json", LEVEL_DIRECTORY);
+        let data = std::fs::read_to_string(&path).unwrap();
+
+        editor.name = "LEVEL01".to_string();
+        editor.save(true);
+        assert_eq!(editor.overwrite, Some(path.clone()));
+
+        // The second SAVE replaces the file, but a headless game doesn't write it
+        editor.save(true);
+        assert_eq!(editor.overwrite, None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), data);
+
+        // A new file doesn't need a confirmation
+        editor.name = "NOT IN THE PACK".to_string();
+        editor.save(true);
+        assert_eq!(editor.overwrite, None);
+    }
+
+    #[test]
+    fn number_keys_select_the_tool() {
+        let mut editor = LevelEditor::new();
+        let bindings = KeyBindings::default();
+
+        assert!(editor.process_board_keys(&key(Keycode::Num3), None, &bindings));
+        assert_eq!(editor.tool, Tool::Goal);
+
+        assert!(editor.process_board_keys(&key(Keycode::Space), None, &bindings));
+        assert_eq!(editor.board.get_tile(editor.cursor), Tile::Goal);
+
+        assert!(!editor.process_board_keys(&key(Keycode::Left), Some(Action::Left), &bindings));
+    }
+
+    #[test]
+    fn bound_keys_are_not_shortcuts() {
+        let mut editor = LevelEditor::new();
+        let bindings = KeyBindings::default();
+
+        // E.g. undo bound to 1 or T
+        assert!(!editor.process_board_keys(&key(Keycode::Num1), Some(Action::Undo), &bindings));
+        assert!(!editor.process_board_keys(&key(Keycode::T), Some(Action::Undo), &bindings));
+        assert_eq!(editor.tool, Tool::Wall);
+        assert!(editor.test_play.is_none());
+    }
+
+    #[test]
+    fn short_status_uses_one_line() {
+        assert_eq!(wrap_status("MIN MOVES: 12"), ("MIN MOVES: 12".to_string(), String::new()));
+    }
+
+    #[test]
+    fn long_status_is_wrapped_at_a_space() {
+        let (first, second) = wrap_status("GOAL AT LINE 12, COLUMN 14 CAN NOT BE REACHED FROM THE START");
+
+        assert_eq!(first, "GOAL AT LINE 12, COLUMN 14 CAN");
+        assert_eq!(second, "NOT BE REACHED FROM THE START");
+    }
+
+    #[test]
+    fn status_line_break_is_kept() {
+        assert_eq!(wrap_status("TEST PLAY\nESC: BACK, R: RESTART"),
+            ("TEST PLAY".to_string(), "ESC: BACK, R: RESTART".to_string()));
+    }
+
+    #[test]
+    fn status_without_spaces_is_cut() {
+        let text = "X".repeat(80);
+        let (first, second) = wrap_status(&text);
+
+        assert_eq!(first.len(), STATUS_LENGTH);
+        assert_eq!(second.len(), STATUS_LENGTH);
+    }
+}
@@ -0,0 +1,206 @@
+// Rust modules
+use std::fs;
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::io::Error as StdIOError;
+
+// External modules
+use serde_derive::{Serialize, Deserialize};
+use serde_json::error::Error as JSONError;
+
+// Local modules
+use crate::level::{Level, LevelError};
+
+// The levels of the campaign
+pub const PACK_PATH: &str = "assets/levels/pack.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelInfo {
+    // Level file name, relative to the pack file
+    pub file: String,
+    // Overrides the name in the level file if not empty
+    #[serde(default)]
+    pub name: String,
+    // Overrides the author in the level file if not empty
+    #[serde(default)]
+    pub author: String,
+    pub par_moves: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LevelPack {
+    pub name: String,
+    pub author: String,
+    pub levels: Vec<LevelInfo>,
+    #[serde(skip)]
+    directory: PathBuf,
+}
+
+impl LevelPack {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<LevelPack, LevelPackError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| LevelPackError::ReadError(e, path.display().to_string()))?;
+        let mut pack: LevelPack = serde_json::from_str(&data)?;
+
+        if pack.levels.is_empty() {
+            return Err(LevelPackError::EmptyPack(path.display().to_string()))
+        }
+
+        pack.directory = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+
+        Ok(pack)
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn level_path(&self, index: usize) -> PathBuf {
+        self.directory.join(&self.levels[index].file)
+    }
+
+    // Loads the level file and applies the meta data from the pack
+    pub fn load_level(&self, index: usize) -> Result<Level, LevelPackError> {
+        let path = self.level_path(index);
+        let info = &self.levels[index];
+        let mut level = Level::load(&path)
+            .map_err(|e| LevelPackError::LevelError(e, path.display().to_string()))?;
+
+        if !info.name.is_empty() {
+            level.name = info.name.clone();
+        }
+
+        if !info.author.is_empty() {
+            level.author = info.author.clone();
+        }
+
+        Ok(level)
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelPackError {
+    ReadError(StdIOError, String),
+    ParseError(JSONError),
+    EmptyPack(String),
+    LevelError(LevelError, String),
+    NoPack,
+}
+
+impl fmt::Display for LevelPackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LevelPackError::ReadError(ref e, ref path) => {
+                write!(f, "IO error while reading the level pack file: '{}', {}", path, e)
+            },
+            LevelPackError::ParseError(ref e) => {
+                write!(f, "Parse error while reading the level pack file: {}", e)
+            },
+            LevelPackError::EmptyPack(ref path) => {
+                write!(f, "Level pack contains no levels: '{}'", path)
+            },
+            LevelPackError::LevelError(ref e, ref path) => {
+                write!(f, "Could not load level '{}': {}", path, e)
+            },
+            LevelPackError::NoPack => {
+                write!(f, "No level pack loaded")
+            },
+        }
+    }
+}
+
+impl error::Error for LevelPackError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            LevelPackError::ReadError(ref e, _) => {
+                Some(e)
+            },
+            LevelPackError::ParseError(ref e) => {
+                Some(e)
+            },
+            LevelPackError::LevelError(ref e, _) => {
+                Some(e)
+            },
+            _ => {
+                None
+            },
+        }
+    }
+}
+
+impl From<JSONError> for LevelPackError {
+    fn from(e: JSONError) -> LevelPackError {
+        LevelPackError::ParseError(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pack(name: &str, data: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_shipped_pack() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+
+        assert_eq!(pack.len(), 4);
+        assert_eq!(pack.level_path(1), Path::new("assets/levels/level02.txt"));
+
+        for index in 0..pack.len() {
+            pack.load_level(index).unwrap();
+        }
+    }
+
+    #[test]
+    fn pack_overrides_level_meta_data() {
+        let path = write_pack("mini_magnets_override_pack.json", &format!(
+            r#"{{ "name": "Test", "author": "Me", "levels": [
+                {{ "file": "{}", "name": "Renamed", "author": "Someone", "par_moves": 3 }}
+            ] }}"#,
+            fs::canonicalize("assets/levels/level02.txt").unwrap().display()));
+        let pack = LevelPack::load(&path).unwrap();
+        let level = pack.load_level(0).unwrap();
+
+        assert_eq!(level.name, "Renamed");
+        assert_eq!(level.author, "Someone");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn pack_errors() {
+        match LevelPack::load("assets/levels/missing_pack.json") {
+            Err(LevelPackError::ReadError(_, _)) => {},
+            _ => panic!("expected ReadError"),
+        }
+
+        let path = write_pack("mini_magnets_empty_pack.json",
+            r#"{ "name": "Empty", "author": "Me", "levels": [] }"#);
+        match LevelPack::load(&path) {
+            Err(LevelPackError::EmptyPack(_)) => {},
+            _ => panic!("expected EmptyPack"),
+        }
+
+        fs::write(&path, "{ \"name\": ").unwrap();
+        match LevelPack::load(&path) {
+            Err(LevelPackError::ParseError(_)) => {},
+            _ => panic!("expected ParseError"),
+        }
+
+        fs::write(&path, r#"{ "name": "Missing", "author": "Me", "levels": [
+            { "file": "no_such_level.json", "par_moves": 1 }
+        ] }"#).unwrap();
+        let pack = LevelPack::load(&path).unwrap();
+        match pack.load_level(0) {
+            Err(LevelPackError::LevelError(_, _)) => {},
+            _ => panic!("expected LevelError"),
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
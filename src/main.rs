@@ -1,4 +1,6 @@
 // Rust modules
+use std::env;
+use std::process;
 
 // External modules
 
@@ -7,11 +9,38 @@ mod game;
 mod settings;
 mod text_fx;
 mod menu;
+mod screen;
 mod main_menu;
 mod audio_menu;
 mod gfx_menu;
 mod credit_menu;
+mod controls_menu;
+mod key_bindings;
+mod input;
 mod high_score;
+mod board;
+mod board_view;
+mod simulation;
+mod history;
+mod solver;
+mod verify;
+mod level_editor;
+mod score;
+mod name_entry;
+mod video;
+mod audio;
+mod level;
+mod level_pack;
+mod campaign;
+mod play_game;
+mod pause_menu;
+mod frame_stats;
+mod render;
+mod golden;
+mod event_source;
+mod script;
+mod replay;
+mod replay_viewer;
 
 use game::{Game};
 
@@ -40,6 +69,38 @@ use game::{Game};
 
 
 pub fn main() {
+     let args: Vec<String> = env::args().collect();
+
+     // Check all levels of a pack before a release:
+     // mini_magnets --verify [assets/levels/pack.json]
+     if args.len() > 1 && args[1] == "--verify" {
+          let path = args.get(2).map(|s| s.as_str()).unwrap_or("assets/levels/pack.json");
+          if !verify::verify_pack(path) {
+               process::exit(1);
+          }
+          return
+     }
+
+     // Draw the screens offscreen and compare them with the golden images:
+     // mini_magnets --golden [--update] [assets/golden]
+     if args.len() > 1 && args[1] == "--golden" {
+          let update = args.iter().any(|arg| arg == "--update");
+          let dir = args[2..].iter().find(|arg| !arg.starts_with("--")).map(|s| s.as_str()).unwrap_or(golden::GOLDEN_DIR);
+          if !golden::check_screens(dir, update) {
+               process::exit(1);
+          }
+          return
+     }
+
+     // Play back scripted input in a headless game and check the screens:
+     // mini_magnets --script assets/scripts/gfx_options.json
+     if args.len() > 2 && args[1] == "--script" {
+          if !script::run_script(&args[2]) {
+               process::exit(1);
+          }
+          return
+     }
+
      match Game::new() {
           Err(e) => {
                println!("SDL init error occured: {}", e);
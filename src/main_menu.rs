@@ -2,18 +2,20 @@
 use std::rc::Rc;
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
 
 // Local modules
-use crate::game::{GameScreen};
+use crate::render::{Renderer};
 use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
 use crate::text_fx::{Font, StaticText};
+use crate::input::{Action};
 
 pub struct MainMenu {
     base: BaseMenu,
+    // Shown instead of the menu after Back was pressed
+    exit_menu: BaseMenu,
+    confirm_exit: bool,
     fps: StaticText,
 }
 
@@ -22,6 +24,7 @@ impl MainMenu {
         MainMenu {
             base: BaseMenu::new(400, 100, 30, "MAIN MENU".to_string(), Vec::new(), vec![
                 "START".to_string(),
+                "LEVEL EDITOR".to_string(),
                 "AUDIO OPTIONS".to_string(),
                 "GFX OPTIONS".to_string(),
                 "CONTROLS".to_string(),
@@ -29,59 +32,116 @@ impl MainMenu {
                 "CREDITS".to_string(),
                 "EXIT".to_string(),
             ]),
+            exit_menu: BaseMenu::new(400, 200, 30, "EXIT GAME?".to_string(), Vec::new(), vec![
+                "NO".to_string(),
+                "YES".to_string(),
+            ]),
+            confirm_exit: false,
             fps: StaticText::new(0, 575, "FPS".to_string()),
         }
     }
 
-    pub fn process(&mut self, event: &Event, quit: &mut bool, game_screen: &mut GameScreen) {
-        match event {
-            Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
-                match self.base.get_selected() {
-                    0 => {
-                        game_screen.start_game();
-                    },
-                    1 => {
-                        game_screen.audio_options();
-                    },
-                    2 => {
-                        game_screen.gfx_options();
-                    },
-                    3 => {
-                        game_screen.controls();
-                    },
-                    4 => {
-                        game_screen.high_score();
-                    },
-                    5 => {
-                        game_screen.credit();
-                    }
-                    6 => {
-                        *quit = true;
-                    }
-                    _ => {
-                        unreachable!();
-                    }
-                }
+    // Back or NO returns to the menu
+    fn process_exit(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let action = match self.exit_menu.process(event, action, context.audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        match action {
+            Action::Confirm if self.exit_menu.get_selected() == 1 => {
+                *context.quit = true;
+            },
+            Action::Confirm | Action::Back => {
+                self.exit_menu.back(context.audio);
+                self.confirm_exit = false;
             },
             _ => {
-                self.base.process(event);
+            }
+        }
+    }
+}
+
+impl Screen for MainMenu {
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        if self.confirm_exit {
+            self.process_exit(event, action, context);
+            return
+        }
+
+        let action = match self.base.process(event, action, context.audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        if action == Action::Back {
+            self.base.back(context.audio);
+            self.confirm_exit = true;
+        } else if action == Action::Confirm {
+            match self.base.get_selected() {
+                0 => {
+                    context.screen.start_game();
+                },
+                1 => {
+                    context.screen.level_editor();
+                },
+                2 => {
+                    context.screen.audio_options();
+                },
+                3 => {
+                    context.screen.gfx_options();
+                },
+                4 => {
+                    context.screen.controls();
+                },
+                5 => {
+                    context.screen.high_score();
+                },
+                6 => {
+                    context.screen.credit();
+                }
+                7 => {
+                    *context.quit = true;
+                }
+                _ => {
+                    unreachable!();
+                }
             }
         }
     }
 
-    pub fn update(&mut self, fps: u32) {
+    fn update(&mut self, context: &mut ScreenContext) {
         self.base.update();
-        let fps_string = format!("FPS: {}", fps);
+        self.exit_menu.update();
+        let fps_string = format!("FPS: {}", context.fps);
         self.fps.set_text(fps_string);
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        self.base.draw(canvas);
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        if self.confirm_exit {
+            self.exit_menu.draw(canvas, alpha);
+        } else {
+            self.base.draw(canvas, alpha);
+        }
+
         self.fps.draw(canvas);
     }
 
-    pub fn set_font(&mut self, font: &Rc<Font>) {
+    fn selected_item(&self) -> Option<usize> {
+        if self.confirm_exit {
+            Some(self.exit_menu.get_selected())
+        } else {
+            Some(self.base.get_selected())
+        }
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
         self.base.set_font(font);
+        self.exit_menu.set_font(font);
         self.fps.set_font(font);
     }
 }
@@ -2,13 +2,17 @@
 use std::rc::Rc;
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
 
 // Local modules
+use crate::render::{Renderer};
 use crate::text_fx::{Font, StaticText, WaveVText, SelectableText};
+use crate::audio::{Audio, Sfx};
+use crate::input::{Action};
+
+// Selecting a menu entry with this text plays the back sound instead of confirm
+const BACK_ENTRY: &str = "BACK";
 
 #[derive(Debug)]
 pub struct MenuItem {
@@ -47,6 +51,11 @@ pub struct BaseMenu {
     title: WaveVText,
     text: Vec<StaticText>,
     menu: Vec<SelectableText>,
+    back_index: Option<usize>,
+    // Entries with a value that Left / Right change
+    value_items: Vec<usize>,
+    // Last mouse position, the wheel events don't have one
+    pointer: Option<(i32, i32)>,
 }
 
 impl BaseMenu {
@@ -60,6 +69,7 @@ impl BaseMenu {
 
         y2 += step;
 
+        let back_index = menu.iter().position(|item| item == BACK_ENTRY);
         let menu = BaseMenu::create_menu(x, &mut y2, step, menu);
         let menu_item = MenuItem::new(menu.len());
 
@@ -69,6 +79,9 @@ impl BaseMenu {
             title,
             text,
             menu,
+            back_index,
+            value_items: Vec::new(),
+            pointer: None,
         }
     }
 
@@ -106,9 +119,14 @@ impl BaseMenu {
         result
     }
 
-    pub fn process(&mut self, event: &Event) {
-        match event {
-            Event::KeyDown { keycode: Some(Keycode::Up), .. } => {
+    // Moves the selection and plays the menu sounds.
+    // Must be called for every event, the menus handle the returned action themselves.
+    // Mouse and touch events are turned into actions for the entry under the pointer.
+    pub fn process(&mut self, event: &Event, action: Option<Action>, audio: &Audio) -> Option<Action> {
+        let action = self.pointer_action(event, audio).or(action)?;
+
+        match action {
+            Action::Up => {
                 let old = self.menu_item.selected;
                 self.menu_item.up();
                 let new = self.menu_item.selected;
@@ -116,9 +134,10 @@ impl BaseMenu {
                 if old != new {
                     self.menu[old].set_active(false);
                     self.menu[new].set_active(true);
+                    audio.play_sfx(Sfx::MenuMove);
                 }
             },
-            Event::KeyDown { keycode: Some(Keycode::Down), .. } => {
+            Action::Down => {
                 let old = self.menu_item.selected;
                 self.menu_item.down();
                 let new = self.menu_item.selected;
@@ -126,10 +145,96 @@ impl BaseMenu {
                 if old != new {
                     self.menu[old].set_active(false);
                     self.menu[new].set_active(true);
+                    audio.play_sfx(Sfx::MenuMove);
+                }
+            },
+            Action::Confirm => {
+                if self.back_index == Some(self.menu_item.selected) {
+                    audio.play_sfx(Sfx::MenuBack);
+                } else {
+                    audio.play_sfx(Sfx::MenuConfirm);
                 }
             },
             _ => {}
         }
+
+        Some(action)
+    }
+
+    // Hovering an entry selects it and a click confirms it.
+    // The value of an entry changes with the wheel or a click on its left / right half,
+    // anywhere else the wheel moves the selection.
+    fn pointer_action(&mut self, event: &Event, audio: &Audio) -> Option<Action> {
+        match *event {
+            Event::MouseMotion { x, y, .. } => {
+                self.pointer = Some((x, y));
+                let index = self.item_at(x, y)?;
+                self.select(index, audio);
+                None
+            },
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                self.pointer = Some((x, y));
+                let index = self.item_at(x, y)?;
+                self.select(index, audio);
+
+                if !self.value_items.contains(&index) {
+                    Some(Action::Confirm)
+                } else if x < self.x as i32 {
+                    Some(Action::Left)
+                } else {
+                    Some(Action::Right)
+                }
+            },
+            Event::MouseWheel { y, .. } if y != 0 => {
+                let index = self.pointer.and_then(|(pointer_x, pointer_y)| self.item_at(pointer_x, pointer_y));
+
+                match index {
+                    Some(index) if self.value_items.contains(&index) => {
+                        self.select(index, audio);
+                        Some(if y > 0 { Action::Right } else { Action::Left })
+                    },
+                    _ => {
+                        Some(if y > 0 { Action::Up } else { Action::Down })
+                    }
+                }
+            },
+            _ => None,
+        }
+    }
+
+    fn select(&mut self, index: usize, audio: &Audio) {
+        let old = self.menu_item.selected;
+
+        if old != index {
+            self.menu_item.selected = index;
+            self.menu[old].set_active(false);
+            self.menu[index].set_active(true);
+            audio.play_sfx(Sfx::MenuMove);
+        }
+    }
+
+    // Index of the entry at the logical position
+    pub fn item_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.menu.iter().position(|item| item.contains(x, y))
+    }
+
+    // Marks the entries that have a value, see pointer_action()
+    pub fn set_value_items(&mut self, value_items: Vec<usize>) {
+        self.value_items = value_items;
+    }
+
+    // Feedback for Left / Right on a value, an error sound if the value is at its limit.
+    // Call it after the new value is active so a volume change can be heard.
+    pub fn value_changed(&self, audio: &Audio, changed: bool) {
+        if changed {
+            audio.play_sfx(Sfx::ValueChange);
+        } else {
+            audio.play_sfx(Sfx::Error);
+        }
+    }
+
+    pub fn back(&self, audio: &Audio) {
+        audio.play_sfx(Sfx::MenuBack);
     }
 
     pub fn update(&mut self) {
@@ -140,15 +245,15 @@ impl BaseMenu {
         }
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        self.title.draw(canvas);
+    pub fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.title.draw(canvas, alpha);
 
         for item in self.text.iter() {
             item.draw(canvas);
         }
 
         for item in self.menu.iter() {
-            item.draw(canvas);
+            item.draw(canvas, alpha);
         }
     }
 
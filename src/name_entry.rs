@@ -0,0 +1,251 @@
+// Rust modules
+use std::rc::Rc;
+
+// External modules
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::rect::Rect;
+use sdl2::pixels::Color;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::screen::{Screen, ScreenContext};
+use crate::text_fx::{Font, StaticText, WaveVText};
+use crate::input::{Action};
+use crate::replay::{Replay};
+use crate::high_score;
+
+// Characters that can be picked, all of them are in the bitmap font
+const CHARACTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 .-!";
+const COLUMNS: usize = 10;
+const CHAR_ROWS: usize = 4;
+const MAX_NAME_LENGTH: usize = 13;
+const PICKER_X: u32 = 160;
+const PICKER_Y: u32 = 260;
+const CELL_WIDTH: u32 = 48;
+const CELL_HEIGHT: u32 = 40;
+
+// The last picker row only contains the two buttons
+const DELETE: usize = 0;
+const END: usize = 1;
+
+pub struct NameEntry {
+    title: WaveVText,
+    score_text: StaticText,
+    name_text: StaticText,
+    characters: Vec<StaticText>,
+    delete_text: StaticText,
+    end_text: StaticText,
+    row: usize,
+    col: usize,
+    name: String,
+    score: u32,
+    // Saved together with the name
+    replay: Option<Replay>,
+    entry: Option<(u32, String)>,
+}
+
+impl NameEntry {
+    pub fn new() -> NameEntry {
+        let characters = CHARACTERS.chars().enumerate().map(|(i, c)| {
+            let (x, y) = cell_position(i / COLUMNS, i % COLUMNS);
+            StaticText::new(x, y, c.to_string())
+        }).collect();
+
+        let (delete_x, delete_y) = cell_position(CHAR_ROWS, 1);
+        let (end_x, end_y) = cell_position(CHAR_ROWS, 6);
+
+        NameEntry {
+            title: WaveVText::new(400, 100, 15.0, 0.1, 0.5, "NEW HIGH SCORE!".to_string()),
+            score_text: StaticText::new(400, 160, String::new()),
+            name_text: StaticText::new(400, 200, String::new()),
+            characters,
+            delete_text: StaticText::new(delete_x, delete_y, "DEL".to_string()),
+            end_text: StaticText::new(end_x, end_y, "END".to_string()),
+            row: 0,
+            col: 0,
+            name: String::new(),
+            score: 0,
+            replay: None,
+            entry: None,
+        }
+    }
+
+    // Prepare for a new name with the given score
+    fn start(&mut self, score: u32, replay: Replay) {
+        self.score = score;
+        self.replay = Some(replay);
+        self.name.clear();
+        self.row = 0;
+        self.col = 0;
+        self.score_text.set_text(format!("SCORE: {}", score));
+        self.score_text.set_x(400);
+        self.score_text.center();
+    }
+
+    fn row_length(&self) -> usize {
+        if self.row < CHAR_ROWS { COLUMNS } else { 2 }
+    }
+
+    // Moves down by delta rows and wraps around, the column is mapped
+    // between the character rows and the button row
+    fn change_row(&mut self, delta: usize) {
+        let new_row = (self.row + delta) % (CHAR_ROWS + 1);
+
+        if self.row < CHAR_ROWS && new_row == CHAR_ROWS {
+            self.col = if self.col < COLUMNS / 2 { DELETE } else { END };
+        } else if self.row == CHAR_ROWS && new_row < CHAR_ROWS {
+            self.col = if self.col == DELETE { 2 } else { 7 };
+        }
+
+        self.row = new_row;
+    }
+
+    fn select(&mut self) {
+        if self.row < CHAR_ROWS {
+            let index = self.row * COLUMNS + self.col;
+            if let Some(c) = CHARACTERS.chars().nth(index) {
+                self.add_char(c);
+            }
+        } else if self.col == DELETE {
+            self.name.pop();
+        } else {
+            self.finish();
+        }
+    }
+
+    fn add_char(&mut self, c: char) {
+        if self.name.len() < MAX_NAME_LENGTH {
+            self.name.push(c);
+        }
+    }
+
+    fn finish(&mut self) {
+        let name = self.name.trim();
+        let name = if name.is_empty() { "PLAYER" } else { name };
+        self.entry = Some((self.score, name.to_string()));
+    }
+}
+
+impl Screen for NameEntry {
+    // Gets the score and the replay of the run that has just ended
+    fn enter(&mut self, context: &mut ScreenContext) {
+        if let Some((score, replay)) = context.finished_run.take() {
+            self.start(score, replay);
+        }
+    }
+
+    // Needs the raw events for typing
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => {
+                self.name.pop();
+                return
+            },
+            Event::TextInput { text, .. } => {
+                for c in text.to_uppercase().chars() {
+                    if CHARACTERS.contains(c) {
+                        self.add_char(c);
+                    }
+                }
+                return
+            },
+            _ => {
+            }
+        }
+
+        match action {
+            Some(Action::Left) => {
+                let cols = self.row_length();
+                self.col = (self.col + cols - 1) % cols;
+            },
+            Some(Action::Right) => {
+                self.col = (self.col + 1) % self.row_length();
+            },
+            Some(Action::Up) => {
+                self.change_row(CHAR_ROWS);
+            },
+            Some(Action::Down) => {
+                self.change_row(1);
+            },
+            Some(Action::Confirm) => {
+                self.select();
+            },
+            Some(Action::Back) => {
+                self.finish();
+            },
+            _ => {
+            }
+        }
+
+        // The table replaces the name entry, Back returns to the main menu
+        if let Some((score, name)) = self.entry.take() {
+            let replay = self.replay.take().filter(|_| !context.headless).and_then(|replay| {
+                replay.save_new().map_err(|e| println!("Could not save replay: {}", e)).ok()
+            });
+
+            context.high_score.insert(score, name, replay);
+
+            // Saved right away, the replays of dropped entries are deleted with it
+            if !context.headless {
+                if let Err(e) = context.high_score.save(high_score::FILE_PATH) {
+                    println!("Could not save high score table: {}", e);
+                }
+            }
+            context.screen.back();
+            context.screen.high_score();
+        }
+    }
+
+    fn update(&mut self, _context: &mut ScreenContext) {
+        self.title.update();
+        self.name_text.set_text(format!("{}_", self.name));
+        self.name_text.set_x(400);
+        self.name_text.center();
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.title.draw(canvas, alpha);
+        self.score_text.draw(canvas);
+        self.name_text.draw(canvas);
+
+        for item in self.characters.iter() {
+            item.draw(canvas);
+        }
+
+        self.delete_text.draw(canvas);
+        self.end_text.draw(canvas);
+
+        let (x, y, width) = if self.row < CHAR_ROWS {
+            let (x, y) = cell_position(self.row, self.col);
+            (x, y, CELL_WIDTH / 2)
+        } else if self.col == DELETE {
+            let (x, y) = cell_position(CHAR_ROWS, 1);
+            (x, y, self.delete_text.get_width())
+        } else {
+            let (x, y) = cell_position(CHAR_ROWS, 6);
+            (x, y, self.end_text.get_width())
+        };
+
+        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        canvas.draw_rect(Rect::new((x as i32) - 4, (y as i32) - 4, width + 8, 32)).unwrap();
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.title.set_font(font);
+        self.title.center();
+        self.score_text.set_font(font);
+        self.name_text.set_font(font);
+
+        for item in self.characters.iter_mut() {
+            item.set_font(font);
+        }
+
+        self.delete_text.set_font(font);
+        self.end_text.set_font(font);
+    }
+}
+
+fn cell_position(row: usize, col: usize) -> (u32, u32) {
+    (PICKER_X + (col as u32) * CELL_WIDTH, PICKER_Y + (row as u32) * CELL_HEIGHT)
+}
@@ -0,0 +1,109 @@
+// Rust modules
+use std::rc::Rc;
+
+// External modules
+use sdl2::render::{BlendMode};
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::menu::{BaseMenu};
+use crate::screen::{Screen, ScreenContext};
+use crate::play_game;
+use crate::text_fx::{Font};
+use crate::audio::{Playlist};
+use crate::input::{Action};
+
+// Shown on top of the running level, Back returns to the game
+pub struct PauseMenu {
+    base: BaseMenu,
+}
+
+impl PauseMenu {
+    pub fn new() -> PauseMenu {
+        PauseMenu {
+            base: BaseMenu::new(400, 150, 30, "PAUSE".to_string(), Vec::new(), vec![
+                "RESUME".to_string(),
+                "AUDIO OPTIONS".to_string(),
+                "GFX OPTIONS".to_string(),
+                "CONTROLS".to_string(),
+                "QUIT TO MAIN MENU".to_string(),
+            ]),
+        }
+    }
+}
+
+impl Screen for PauseMenu {
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let action = match self.base.process(event, action, context.audio) {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        match action {
+            Action::Back | Action::Pause => {
+                self.base.back(context.audio);
+                context.screen.back();
+            },
+            Action::Confirm => {
+                match self.base.get_selected() {
+                    0 => {
+                        context.screen.back();
+                    },
+                    1 => {
+                        context.screen.audio_options();
+                    },
+                    2 => {
+                        context.screen.gfx_options();
+                    },
+                    3 => {
+                        context.screen.controls();
+                    },
+                    4 => {
+                        // Ends the run, see PlayGame::exit()
+                        context.screen.main_menu();
+                    },
+                    _ => {
+                        unreachable!();
+                    }
+                }
+            },
+            _ => {
+            }
+        }
+    }
+
+    fn update(&mut self, _context: &mut ScreenContext) {
+        self.base.update()
+    }
+
+    // Darkens the level behind the menu
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 192));
+        canvas.fill_rect(None).unwrap();
+        canvas.set_blend_mode(BlendMode::None);
+
+        self.base.draw(canvas, alpha)
+    }
+
+    fn selected_item(&self) -> Option<usize> {
+        Some(self.base.get_selected())
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.base.set_font(font);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    // The music of the level keeps playing
+    fn playlist(&self) -> Playlist {
+        play_game::playlist()
+    }
+}
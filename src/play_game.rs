@@ -0,0 +1,337 @@
+// Rust modules
+use std::rc::Rc;
+use std::path::Path;
+use std::mem;
+use std::time::Duration;
+
+// External modules
+use sdl2::event::Event;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::game::{UPDATE_STEP};
+use crate::board::{Board, Direction};
+use crate::board_view::{BoardView};
+use crate::simulation::{self, BoardEvent};
+use crate::history::{History};
+use crate::level::{Level};
+use crate::level_pack::{LevelPackError};
+use crate::campaign::{Campaign};
+use crate::settings::{GameSettings};
+use crate::score::{LevelResult};
+use crate::replay::{Replay, LevelReplay, ReplayInput};
+use crate::screen::{Screen, ScreenContext};
+use crate::text_fx::{Font, StaticText, WaveVText};
+use crate::video::{LOGICAL_WIDTH, LOGICAL_HEIGHT};
+use crate::audio::{Audio, Sfx, Playlist};
+use crate::input::{Action};
+
+const CELL_SIZE: u32 = 32;
+
+// The music while playing, also used by the pause menu
+pub fn playlist() -> Playlist {
+    Playlist::new(&["game1", "game2", "game3"], true)
+}
+
+pub struct PlayGame {
+    campaign: Campaign,
+    board: Board,
+    start_board: Board,
+    board_view: BoardView,
+    history: History,
+    moves: u32,
+    undos: u32,
+    par_moves: u32,
+    solved: bool,
+    // Time spent in the level, only counted while it is on top of the screens,
+    // so the pause menu doesn't cost any points
+    level_time: Duration,
+    // Score of all levels solved since START was selected
+    run_score: u32,
+    // The solved levels of the run and the inputs of the current level
+    replay: Replay,
+    level_replay: LevelReplay,
+    title: WaveVText,
+    score_text: StaticText,
+    author: StaticText,
+    info: StaticText,
+    message: StaticText,
+}
+
+impl PlayGame {
+    pub fn new() -> PlayGame {
+        PlayGame {
+            campaign: Campaign::new(),
+            board: Board::new(0, 0),
+            start_board: Board::new(0, 0),
+            board_view: BoardView::new(CELL_SIZE),
+            history: History::new(),
+            moves: 0,
+            undos: 0,
+            par_moves: 0,
+            solved: false,
+            level_time: Duration::from_secs(0),
+            run_score: 0,
+            replay: Replay::new(),
+            level_replay: LevelReplay::new(0, String::new()),
+            title: WaveVText::new(400, 20, 15.0, 0.1, 0.5, String::new()),
+            score_text: StaticText::new(20, 515, String::new()),
+            author: StaticText::new(20, 545, String::new()),
+            info: StaticText::new(20, 575, "MOVES: 0".to_string()),
+            message: StaticText::new(400, 500, "WELL DONE! PRESS RETURN".to_string()),
+        }
+    }
+
+    pub fn load_pack<T: AsRef<Path>>(&mut self, path: T) -> Result<(), LevelPackError> {
+        self.campaign.load_pack(path)
+    }
+
+    // Shows the first level that is not solved yet
+    fn resume_level(&mut self, settings: &GameSettings) -> Result<(), LevelPackError> {
+        let level = self.campaign.resume(settings)?;
+        self.set_level(level);
+
+        Ok(())
+    }
+
+    // Called when a new game is started from the main menu
+    fn start(&mut self, context: &mut ScreenContext) {
+        self.run_score = 0;
+        self.replay = Replay::new();
+
+        if let Err(e) = self.resume_level(context.settings) {
+            println!("Could not start game: {}", e);
+            context.screen.main_menu();
+        }
+    }
+
+    // A good enough score is entered into the high score table
+    fn end_run(&mut self, context: &mut ScreenContext) {
+        if context.high_score.qualifies(self.run_score) {
+            *context.finished_run = Some((self.run_score, mem::replace(&mut self.replay, Replay::new())));
+            context.screen.name_entry();
+        }
+
+        self.run_score = 0;
+    }
+
+    fn set_level(&mut self, level: Level) {
+        self.start_board = level.board;
+        self.par_moves = self.campaign.get_par_moves();
+        self.title.set_text(format!("{} - {}", self.campaign.get_current() + 1, level.name.to_uppercase()));
+        self.title.set_x(400);
+        self.title.center();
+        self.author.set_text(format!("BY {}", level.author.to_uppercase()));
+        self.undos = 0;
+        self.level_time = Duration::from_secs(0);
+        self.level_replay = LevelReplay::new(self.campaign.get_current(), self.campaign.get_file());
+        self.restart();
+    }
+
+    // Inputs are timed from the level start, like the score
+    fn record(&mut self, input: ReplayInput) {
+        let time = self.level_time.as_millis() as u32;
+        self.level_replay.push(input, time);
+    }
+
+    fn move_player(&mut self, direction: Direction, settings: &mut GameSettings, audio: &Audio) {
+        if self.solved {
+            return
+        }
+
+        let result = simulation::apply_move(&self.board, direction);
+
+        if result.has_moved() {
+            self.record(ReplayInput::Move(direction));
+            self.board_view.start_animation(&result.events);
+            let previous = mem::replace(&mut self.board, result.board);
+            self.history.push(previous);
+            self.moves += 1;
+            self.solved = self.board.is_solved();
+            audio.play_sfx(move_sfx(&result.events, self.solved));
+
+            if self.solved {
+                self.campaign.complete_level(settings);
+
+                let result = LevelResult {
+                    level: self.campaign.get_current(),
+                    moves: self.moves,
+                    par_moves: self.par_moves,
+                    undos: self.undos,
+                    seconds: self.level_replay.seconds(),
+                };
+                let score = result.score();
+                self.run_score += score;
+
+                self.replay.levels.push(self.level_replay.clone());
+                self.replay.score = self.run_score;
+
+                self.message.set_text(format!("+{} POINTS! PRESS RETURN", score));
+                self.message.set_x(400);
+                self.message.center();
+            }
+        }
+    }
+
+    fn undo(&mut self) {
+        if self.solved {
+            return
+        }
+
+        if let Some(board) = self.history.undo(&self.board) {
+            self.record(ReplayInput::Undo);
+            self.board = board;
+            self.board_view.stop_animation();
+            self.moves -= 1;
+            self.undos += 1;
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.solved {
+            return
+        }
+
+        if let Some(board) = self.history.redo(&self.board) {
+            self.record(ReplayInput::Redo);
+            self.board = board;
+            self.board_view.stop_animation();
+            self.moves += 1;
+        }
+    }
+
+    // Back to the start of the level, the undo counter is kept for scoring
+    fn restart(&mut self) {
+        self.board = self.start_board.clone();
+        self.board_view.stop_animation();
+        self.history.reset(&self.board);
+        self.moves = 0;
+        self.solved = false;
+    }
+}
+
+// The most important thing that happened in a move
+fn move_sfx(events: &[BoardEvent], solved: bool) -> Sfx {
+    let merged = events.iter().any(|event| matches!(event, BoardEvent::Merged(..)));
+    let pushed = events.iter().any(|event| matches!(event, BoardEvent::PieceMoved(..)));
+
+    if solved {
+        Sfx::LevelSolved
+    } else if merged {
+        Sfx::Merge
+    } else if pushed {
+        Sfx::PiecePush
+    } else {
+        Sfx::PlayerMove
+    }
+}
+
+impl Screen for PlayGame {
+    // A new game is started from the main menu
+    fn enter(&mut self, context: &mut ScreenContext) {
+        self.start(context);
+    }
+
+    // The run ends when the level is left, returning from the pause menu doesn't count
+    fn exit(&mut self, context: &mut ScreenContext) {
+        self.end_run(context);
+    }
+
+    fn process(&mut self, _event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let action = match action {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        let settings = &mut *context.settings;
+        let audio = &*context.audio;
+
+        match action {
+            Action::Back | Action::Pause => {
+                context.screen.pause();
+            },
+            Action::Confirm if self.solved => {
+                match self.campaign.next() {
+                    Some(Ok(level)) => {
+                        self.set_level(level);
+                    },
+                    Some(Err(e)) => {
+                        println!("Could not load next level: {}", e);
+                        context.screen.main_menu();
+                    },
+                    None => {
+                        // Last level of the pack solved
+                        context.screen.main_menu();
+                    }
+                }
+            },
+            Action::Up => {
+                self.move_player(Direction::Up, settings, audio);
+            },
+            Action::Down => {
+                self.move_player(Direction::Down, settings, audio);
+            },
+            Action::Left => {
+                self.move_player(Direction::Left, settings, audio);
+            },
+            Action::Right => {
+                self.move_player(Direction::Right, settings, audio);
+            },
+            Action::Undo => {
+                self.undo();
+            },
+            Action::Redo => {
+                self.redo();
+            },
+            Action::Restart if !self.solved => {
+                self.record(ReplayInput::Restart);
+                self.restart();
+            },
+            _ => {
+            }
+        }
+    }
+
+    // Not called while the pause menu is open
+    fn update(&mut self, _context: &mut ScreenContext) {
+        if !self.solved {
+            self.level_time += UPDATE_STEP;
+        }
+
+        self.title.update();
+        self.board_view.update();
+        self.info.set_text(format!("MOVES: {}  PAR: {}  UNDOS: {}", self.moves, self.par_moves, self.undos));
+        self.score_text.set_text(format!("SCORE: {}", self.run_score));
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        let x = (LOGICAL_WIDTH as i32 - self.board_view.get_width(&self.board) as i32) / 2;
+        let y = (LOGICAL_HEIGHT as i32 - self.board_view.get_height(&self.board) as i32) / 2;
+
+        self.board_view.draw(canvas, &self.board, x, y, alpha);
+        self.title.draw(canvas, alpha);
+        self.score_text.draw(canvas);
+        self.author.draw(canvas);
+        self.info.draw(canvas);
+
+        if self.solved {
+            self.message.draw(canvas);
+        }
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.title.set_font(font);
+        self.title.center();
+        self.score_text.set_font(font);
+        self.author.set_font(font);
+        self.info.set_font(font);
+        self.message.set_font(font);
+        self.message.center();
+    }
+
+    fn playlist(&self) -> Playlist {
+        playlist()
+    }
+}
@@ -0,0 +1,165 @@
+// Rust modules
+use std::rc::Rc;
+use std::path::Path;
+
+// External modules
+use sdl2::render::{Canvas, RenderTarget, Texture, TextureCreator, BlendMode};
+use sdl2::surface::{Surface, SurfaceContext};
+use sdl2::image::{SaveSurface, LoadSurface};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Rect, Point};
+
+// Local modules
+use crate::frame_stats;
+use crate::text_fx::{Font};
+
+// Everything the screens draw with. Implemented for every SDL canvas,
+// so the same code draws into the window or into an offscreen surface.
+pub trait Renderer {
+    fn set_draw_color(&mut self, color: Color);
+
+    fn set_blend_mode(&mut self, blend: BlendMode);
+
+    fn clear(&mut self);
+
+    // None fills the whole target
+    fn fill_rect(&mut self, rect: Option<Rect>) -> Result<(), String>;
+
+    fn draw_rect(&mut self, rect: Rect) -> Result<(), String>;
+
+    fn draw_line(&mut self, start: Point, end: Point) -> Result<(), String>;
+
+    fn copy(&mut self, texture: &Texture, source: Option<Rect>, destination: Option<Rect>) -> Result<(), String>;
+}
+
+// Every operation that sends something to the renderer is counted as a draw call
+impl<T: RenderTarget> Renderer for Canvas<T> {
+    fn set_draw_color(&mut self, color: Color) {
+        Canvas::set_draw_color(self, color);
+    }
+
+    fn set_blend_mode(&mut self, blend: BlendMode) {
+        Canvas::set_blend_mode(self, blend);
+    }
+
+    fn clear(&mut self) {
+        Canvas::clear(self);
+    }
+
+    fn fill_rect(&mut self, rect: Option<Rect>) -> Result<(), String> {
+        frame_stats::count_draw_call();
+        Canvas::fill_rect(self, rect)
+    }
+
+    fn draw_rect(&mut self, rect: Rect) -> Result<(), String> {
+        frame_stats::count_draw_call();
+        Canvas::draw_rect(self, rect)
+    }
+
+    fn draw_line(&mut self, start: Point, end: Point) -> Result<(), String> {
+        frame_stats::count_draw_call();
+        Canvas::draw_line(self, start, end)
+    }
+
+    fn copy(&mut self, texture: &Texture, source: Option<Rect>, destination: Option<Rect>) -> Result<(), String> {
+        frame_stats::count_draw_call();
+        Canvas::copy(self, texture, source, destination)
+    }
+}
+
+// Pixel format of the offscreen surface and the saved images
+const FORMAT: PixelFormatEnum = PixelFormatEnum::ARGB8888;
+
+// Result of comparing a drawn image with the expected one
+#[derive(Debug, Clone, Copy)]
+pub struct ImageDiff {
+    // Pixels where a color channel differs more than the tolerance
+    pub different_pixels: usize,
+    pub max_difference: u8,
+}
+
+impl ImageDiff {
+    pub fn matches(&self) -> bool {
+        self.different_pixels == 0
+    }
+}
+
+// A software renderer drawing into a surface in memory,
+// it needs neither a display nor a GPU
+pub struct Offscreen {
+    canvas: Canvas<Surface<'static>>,
+    texture_creator: TextureCreator<SurfaceContext<'static>>,
+}
+
+impl Offscreen {
+    pub fn new(width: u32, height: u32) -> Result<Offscreen, String> {
+        let surface = Surface::new(width, height, FORMAT)?;
+        let canvas = surface.into_canvas()?;
+        let texture_creator = canvas.texture_creator();
+
+        Ok(Offscreen {
+            canvas,
+            texture_creator,
+        })
+    }
+
+    // Textures only work with the renderer that created them,
+    // so the font has to be loaded again for the offscreen surface
+    pub fn load_font<P: AsRef<Path>>(&self, path: P, char_width: u32, char_height: u32) -> Result<Rc<Font>, String> {
+        Font::load(&self.texture_creator, path, char_width, char_height).map(Rc::new)
+    }
+
+    pub fn renderer(&mut self) -> &mut dyn Renderer {
+        &mut self.canvas
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let (width, height) = self.canvas.output_size()?;
+        let mut pixels = self.read_pixels()?;
+        let surface = Surface::from_data(&mut pixels, width, height, width * (FORMAT.byte_size_per_pixel() as u32), FORMAT)?;
+
+        surface.save(path)
+    }
+
+    // Compares the surface with a saved image, each color channel may differ by the tolerance
+    pub fn compare<P: AsRef<Path>>(&self, path: P, tolerance: u8) -> Result<ImageDiff, String> {
+        let (width, height) = self.canvas.output_size()?;
+        let expected = Surface::from_file(path)?.convert(&self.canvas.surface().pixel_format())?;
+
+        if expected.size() != (width, height) {
+            return Err(format!("size is {:?}, expected {:?}", (width, height), expected.size()))
+        }
+
+        let bytes_per_pixel = FORMAT.byte_size_per_pixel();
+        let actual_pitch = (width as usize) * bytes_per_pixel;
+        let expected_pitch = expected.pitch() as usize;
+        let actual_pixels = self.read_pixels()?;
+
+        let mut diff = ImageDiff {
+            different_pixels: 0,
+            max_difference: 0,
+        };
+
+        expected.with_lock(|expected_pixels| {
+            for y in 0..(height as usize) {
+                for x in 0..(width as usize) {
+                    let a = &actual_pixels[(y * actual_pitch) + (x * bytes_per_pixel)..][..bytes_per_pixel];
+                    let e = &expected_pixels[(y * expected_pitch) + (x * bytes_per_pixel)..][..bytes_per_pixel];
+                    let difference = a.iter().zip(e.iter()).map(|(a, e)| a.abs_diff(*e)).max().unwrap_or(0);
+
+                    if difference > tolerance {
+                        diff.different_pixels += 1;
+                    }
+
+                    diff.max_difference = diff.max_difference.max(difference);
+                }
+            }
+        });
+
+        Ok(diff)
+    }
+
+    fn read_pixels(&self) -> Result<Vec<u8>, String> {
+        self.canvas.read_pixels(None, FORMAT)
+    }
+}
@@ -0,0 +1,717 @@
+// Rust modules
+use std::fs;
+use std::mem;
+use std::error;
+use std::fmt;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::Error as StdIOError;
+
+// External modules
+use serde_derive::{Serialize, Deserialize};
+use serde_json::error::Error as JSONError;
+
+// Local modules
+use crate::board::{Board, Direction};
+use crate::simulation::{self, BoardEvent};
+use crate::history::{History};
+use crate::level_pack::{LevelPack, LevelPackError};
+use crate::score::{LevelResult};
+
+// Replays of the high score table are stored here
+pub const REPLAY_DIR: &str = "assets/replays";
+
+// Pause between two levels during playback
+const LEVEL_PAUSE_MS: f64 = 1000.0;
+
+// Everything a player can do that changes the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayInput {
+    Move(Direction),
+    Undo,
+    Redo,
+    Restart,
+}
+
+impl ReplayInput {
+    pub fn to_char(self) -> char {
+        match self {
+            ReplayInput::Move(direction) => direction.to_char(),
+            ReplayInput::Undo => '-',
+            ReplayInput::Redo => '+',
+            ReplayInput::Restart => '0',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<ReplayInput> {
+        match c {
+            'L' => Some(ReplayInput::Move(Direction::Left)),
+            'R' => Some(ReplayInput::Move(Direction::Right)),
+            'U' => Some(ReplayInput::Move(Direction::Up)),
+            'D' => Some(ReplayInput::Move(Direction::Down)),
+            '-' => Some(ReplayInput::Undo),
+            '+' => Some(ReplayInput::Redo),
+            '0' => Some(ReplayInput::Restart),
+            _ => None,
+        }
+    }
+}
+
+// All inputs of a single solved level
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelReplay {
+    // Zero based index of the level in the pack, it counts for the score
+    pub level: usize,
+    // Level file name in the pack, to detect a changed pack
+    pub file: String,
+    // One character for each input, see ReplayInput::to_char()
+    pub inputs: String,
+    // Milliseconds played since the level start, one for each input. Time in the pause menu is not counted.
+    pub times: Vec<u32>,
+}
+
+impl LevelReplay {
+    pub fn new(level: usize, file: String) -> LevelReplay {
+        LevelReplay {
+            level,
+            file,
+            inputs: String::new(),
+            times: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, input: ReplayInput, time: u32) {
+        self.inputs.push(input.to_char());
+        self.times.push(time);
+    }
+
+    // Seconds as used for the score, counted until the last input
+    pub fn seconds(&self) -> u32 {
+        self.times.last().map_or(0, |time| time / 1000)
+    }
+
+    fn parse(&self) -> Result<Vec<(ReplayInput, u32)>, ReplayError> {
+        if self.inputs.chars().count() != self.times.len() {
+            return Err(ReplayError::InvalidLevel(self.file.clone()))
+        }
+
+        self.inputs.chars().zip(self.times.iter())
+            .map(|(c, time)| ReplayInput::from_char(c).map(|input| (input, *time)).ok_or(ReplayError::InvalidInput(c)))
+            .collect()
+    }
+}
+
+// The solved levels of one run, from START until the run ended
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Replay {
+    pub score: u32,
+    pub levels: Vec<LevelReplay>,
+}
+
+impl Replay {
+    pub fn new() -> Replay {
+        Replay {
+            score: 0,
+            levels: Vec::new(),
+        }
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Replay, ReplayError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| ReplayError::ReadError(e, path.display().to_string()))?;
+        let replay: Replay = serde_json::from_str(&data)?;
+
+        Ok(replay)
+    }
+
+    // Saves the replay with a new name in the replay directory and returns the path
+    pub fn save_new(&self) -> Result<String, ReplayError> {
+        fs::create_dir_all(REPLAY_DIR)
+            .map_err(|e| ReplayError::WriteError(e, REPLAY_DIR.to_string()))?;
+
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+        let path = format!("{}/{}_{}.json", REPLAY_DIR, seconds, self.score);
+        let data = serde_json::to_string(self)?;
+        fs::write(&path, data)
+            .map_err(|e| ReplayError::WriteError(e, path.clone()))?;
+
+        Ok(path)
+    }
+
+    // Plays every level through the simulation and calculates the score again.
+    // Returns the score if it matches the recorded one.
+    pub fn verify(&self, pack: &LevelPack) -> Result<u32, ReplayError> {
+        let mut score = 0;
+
+        for level in self.levels.iter() {
+            let mut state = ReplayState::new(load_start_board(pack, level)?);
+            let inputs = level.parse()?;
+
+            for (index, (input, _)) in inputs.iter().enumerate() {
+                // Nothing can be done after the level is solved
+                if state.solved {
+                    return Err(ReplayError::NotSolved(level.file.clone(), index))
+                }
+
+                state.apply(*input);
+            }
+
+            if !state.solved {
+                return Err(ReplayError::NotSolved(level.file.clone(), inputs.len()))
+            }
+
+            let result = LevelResult {
+                level: level.level,
+                moves: state.moves,
+                par_moves: pack.levels[level.level].par_moves,
+                undos: state.undos,
+                seconds: level.seconds(),
+            };
+            score += result.score();
+        }
+
+        if score != self.score {
+            return Err(ReplayError::ScoreMismatch(self.score, score))
+        }
+
+        Ok(score)
+    }
+}
+
+fn load_start_board(pack: &LevelPack, level: &LevelReplay) -> Result<Board, ReplayError> {
+    match pack.levels.get(level.level) {
+        Some(info) if info.file == level.file => {
+            Ok(pack.load_level(level.level)?.board)
+        },
+        _ => {
+            Err(ReplayError::LevelMismatch(level.file.clone()))
+        }
+    }
+}
+
+// A level during playback, follows the same rules as the game
+pub struct ReplayState {
+    start: Board,
+    pub board: Board,
+    history: History,
+    pub moves: u32,
+    pub undos: u32,
+    pub solved: bool,
+}
+
+impl ReplayState {
+    pub fn new(start: Board) -> ReplayState {
+        let mut history = History::new();
+        history.reset(&start);
+
+        ReplayState {
+            board: start.clone(),
+            start,
+            history,
+            moves: 0,
+            undos: 0,
+            solved: false,
+        }
+    }
+
+    // Returns the events of a move, they are empty for the other inputs
+    pub fn apply(&mut self, input: ReplayInput) -> Vec<BoardEvent> {
+        if self.solved {
+            return Vec::new()
+        }
+
+        match input {
+            ReplayInput::Move(direction) => {
+                let result = simulation::apply_move(&self.board, direction);
+
+                if result.has_moved() {
+                    let previous = mem::replace(&mut self.board, result.board);
+                    self.history.push(previous);
+                    self.moves += 1;
+                    self.solved = self.board.is_solved();
+                    return result.events
+                }
+            },
+            ReplayInput::Undo => {
+                if let Some(board) = self.history.undo(&self.board) {
+                    self.board = board;
+                    self.moves -= 1;
+                    self.undos += 1;
+                }
+            },
+            ReplayInput::Redo => {
+                if let Some(board) = self.history.redo(&self.board) {
+                    self.board = board;
+                    self.moves += 1;
+                }
+            },
+            ReplayInput::Restart => {
+                // The undo counter is kept for scoring
+                self.board = self.start.clone();
+                self.history.reset(&self.board);
+                self.moves = 0;
+            },
+        }
+
+        Vec::new()
+    }
+}
+
+// Plays a replay in real time, it can be paused, stepped and seeked
+pub struct ReplayPlayer {
+    start_boards: Vec<Board>,
+    inputs: Vec<Vec<(ReplayInput, u32)>>,
+    // Pack index of each level, for the level number
+    level_numbers: Vec<usize>,
+    level: usize,
+    // Number of inputs of the current level that have been applied
+    applied: usize,
+    state: ReplayState,
+    // Milliseconds since the start of the current level
+    time: f64,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: &Replay, pack: &LevelPack) -> Result<ReplayPlayer, ReplayError> {
+        if replay.levels.is_empty() {
+            return Err(ReplayError::Empty)
+        }
+
+        let start_boards = replay.levels.iter()
+            .map(|level| load_start_board(pack, level))
+            .collect::<Result<Vec<Board>, ReplayError>>()?;
+        let inputs = replay.levels.iter()
+            .map(|level| level.parse())
+            .collect::<Result<Vec<_>, ReplayError>>()?;
+
+        Ok(ReplayPlayer {
+            state: ReplayState::new(start_boards[0].clone()),
+            start_boards,
+            inputs,
+            level_numbers: replay.levels.iter().map(|level| level.level).collect(),
+            level: 0,
+            applied: 0,
+            time: 0.0,
+        })
+    }
+
+    // Moves the playback time forward and returns the events of the last move
+    pub fn advance(&mut self, ms: f64) -> Vec<BoardEvent> {
+        self.time += ms;
+
+        let mut events = Vec::new();
+        let inputs = &self.inputs[self.level];
+
+        while self.applied < inputs.len() && (inputs[self.applied].1 as f64) <= self.time {
+            events = self.state.apply(inputs[self.applied].0);
+            self.applied += 1;
+        }
+
+        if self.applied == inputs.len() && self.time >= self.end_time() + LEVEL_PAUSE_MS && self.level + 1 < self.inputs.len() {
+            self.go_to(self.level + 1, 0);
+        }
+
+        events
+    }
+
+    // Applies the next input right away, at the end of a level the next level starts
+    pub fn step_forward(&mut self) -> Vec<BoardEvent> {
+        if self.applied < self.inputs[self.level].len() {
+            let (input, time) = self.inputs[self.level][self.applied];
+            self.applied += 1;
+            self.time = time as f64;
+            self.state.apply(input)
+        } else {
+            if self.level + 1 < self.inputs.len() {
+                self.go_to(self.level + 1, 0);
+            }
+            Vec::new()
+        }
+    }
+
+    // Takes back the last input, at the start of a level the previous level is shown solved
+    pub fn step_back(&mut self) {
+        if self.applied > 0 {
+            self.go_to(self.level, self.applied - 1);
+        } else if self.level > 0 {
+            let previous = self.level - 1;
+            self.go_to(previous, self.inputs[previous].len());
+        }
+    }
+
+    // Jumps within the current level, the board is played again from the start
+    pub fn seek(&mut self, ms: f64) {
+        let time = (self.time + ms).max(0.0).min(self.end_time());
+        let applied = self.inputs[self.level].iter().take_while(|(_, t)| (*t as f64) <= time).count();
+
+        self.go_to(self.level, applied);
+        self.time = time;
+    }
+
+    fn go_to(&mut self, level: usize, applied: usize) {
+        self.level = level;
+        self.state = ReplayState::new(self.start_boards[level].clone());
+
+        for (input, _) in self.inputs[level][..applied].iter() {
+            self.state.apply(*input);
+        }
+
+        self.applied = applied;
+        self.time = if applied == 0 { 0.0 } else { self.inputs[level][applied - 1].1 as f64 };
+    }
+
+    fn end_time(&self) -> f64 {
+        self.inputs[self.level].last().map_or(0.0, |(_, time)| *time as f64)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.level + 1 == self.inputs.len() && self.applied == self.inputs[self.level].len()
+    }
+
+    pub fn get_state(&self) -> &ReplayState {
+        &self.state
+    }
+
+    // Zero based index of the current level in the pack
+    pub fn get_level_number(&self) -> usize {
+        self.level_numbers[self.level]
+    }
+
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    ReadError(StdIOError, String),
+    WriteError(StdIOError, String),
+    ParseError(JSONError),
+    PackError(LevelPackError),
+    Empty,
+    InvalidInput(char),
+    InvalidLevel(String),
+    LevelMismatch(String),
+    NotSolved(String, usize),
+    ScoreMismatch(u32, u32),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::ReadError(ref e, ref path) => {
+                write!(f, "IO error while reading the replay file: '{}', {}", path, e)
+            },
+            ReplayError::WriteError(ref e, ref path) => {
+                write!(f, "IO error while writing the replay file: '{}', {}", path, e)
+            },
+            ReplayError::ParseError(ref e) => {
+                write!(f, "Parse error while accessing the replay file: {}", e)
+            },
+            ReplayError::PackError(ref e) => {
+                write!(f, "Could not load the levels of the replay: {}", e)
+            },
+            ReplayError::Empty => {
+                write!(f, "Replay contains no levels")
+            },
+            ReplayError::InvalidInput(c) => {
+                write!(f, "Invalid input '{}' in replay", c)
+            },
+            ReplayError::InvalidLevel(ref file) => {
+                write!(f, "Inputs and times of level '{}' don't match", file)
+            },
+            ReplayError::LevelMismatch(ref file) => {
+                write!(f, "Level '{}' is not in the level pack", file)
+            },
+            ReplayError::NotSolved(ref file, index) => {
+                write!(f, "Level '{}' is not solved exactly at input {}", file, index)
+            },
+            ReplayError::ScoreMismatch(recorded, calculated) => {
+                write!(f, "Recorded score {} doesn't match the replayed score {}", recorded, calculated)
+            },
+        }
+    }
+}
+
+impl error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ReplayError::ReadError(ref e, _) => {
+                Some(e)
+            },
+            ReplayError::WriteError(ref e, _) => {
+                Some(e)
+            },
+            ReplayError::ParseError(ref e) => {
+                Some(e)
+            },
+            ReplayError::PackError(ref e) => {
+                Some(e)
+            },
+            _ => {
+                None
+            },
+        }
+    }
+}
+
+impl From<JSONError> for ReplayError {
+    fn from(e: JSONError) -> ReplayError {
+        ReplayError::ParseError(e)
+    }
+}
+
+impl From<LevelPackError> for ReplayError {
+    fn from(e: LevelPackError) -> ReplayError {
+        ReplayError::PackError(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level_pack::{PACK_PATH};
+
+    // Half a second between two inputs
+    fn level_replay(level: usize, file: &str, inputs: &str) -> LevelReplay {
+        let mut replay = LevelReplay::new(level, file.to_string());
+
+        for (index, c) in inputs.chars().enumerate() {
+            replay.push(ReplayInput::from_char(c).unwrap(), (index as u32 + 1) * 500);
+        }
+
+        replay
+    }
+
+    // The first two levels of the pack, the first one with an undo and a redo
+    fn replay(pack: &LevelPack) -> Replay {
+        let levels = vec![
+            level_replay(0, "level01.json", "RUU-+RDD"),
+            level_replay(1, "level02.txt", "RRRRURDD"),
+        ];
+        let first = LevelResult { level: 0, moves: 6, par_moves: pack.levels[0].par_moves, undos: 1, seconds: 4 };
+        let second = LevelResult { level: 1, moves: 8, par_moves: pack.levels[1].par_moves, undos: 0, seconds: 4 };
+
+        Replay {
+            score: first.score() + second.score(),
+            levels,
+        }
+    }
+
+    fn player(pack: &LevelPack) -> ReplayPlayer {
+        ReplayPlayer::new(&replay(pack), pack).unwrap()
+    }
+
+    fn board(player: &ReplayPlayer) -> Board {
+        player.get_state().board.clone()
+    }
+
+    #[test]
+    fn verify_good_replay() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let replay = replay(&pack);
+
+        assert_eq!(replay.verify(&pack).unwrap(), replay.score);
+    }
+
+    #[test]
+    fn verify_score_mismatch() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut replay = replay(&pack);
+        let score = replay.score;
+        replay.score += 1;
+
+        match replay.verify(&pack) {
+            Err(ReplayError::ScoreMismatch(recorded, calculated)) => {
+                assert_eq!((recorded, calculated), (score + 1, score));
+            },
+            _ => panic!("expected ScoreMismatch"),
+        }
+    }
+
+    #[test]
+    fn verify_level_mismatch() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut replay = replay(&pack);
+
+        replay.levels[1].file = "level03.json".to_string();
+        match replay.verify(&pack) {
+            Err(ReplayError::LevelMismatch(file)) => assert_eq!(file, "level03.json"),
+            _ => panic!("expected LevelMismatch"),
+        }
+
+        replay.levels[1].level = 10;
+        match ReplayPlayer::new(&replay, &pack) {
+            Err(ReplayError::LevelMismatch(_)) => {},
+            _ => panic!("expected LevelMismatch"),
+        }
+    }
+
+    #[test]
+    fn verify_level_not_solved() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut replay = replay(&pack);
+
+        replay.levels[0] = level_replay(0, "level01.json", "RUUR");
+        match replay.verify(&pack) {
+            Err(ReplayError::NotSolved(file, index)) => assert_eq!((file.as_str(), index), ("level01.json", 4)),
+            _ => panic!("expected NotSolved"),
+        }
+
+        // Inputs after the level is solved are not allowed either
+        replay.levels[0] = level_replay(0, "level01.json", "RUURDDL");
+        match replay.verify(&pack) {
+            Err(ReplayError::NotSolved(_, index)) => assert_eq!(index, 6),
+            _ => panic!("expected NotSolved"),
+        }
+    }
+
+    #[test]
+    fn verify_invalid_inputs() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut replay = replay(&pack);
+
+        replay.levels[0].inputs.push('X');
+        replay.levels[0].times.push(5000);
+        match replay.verify(&pack) {
+            Err(ReplayError::InvalidInput('X')) => {},
+            _ => panic!("expected InvalidInput"),
+        }
+
+        replay.levels[0].times.pop();
+        match replay.verify(&pack) {
+            Err(ReplayError::InvalidLevel(_)) => {},
+            _ => panic!("expected InvalidLevel"),
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let start = pack.load_level(1).unwrap().board;
+        let mut state = ReplayState::new(start.clone());
+
+        state.apply(ReplayInput::Move(Direction::Right));
+        let first = state.board.clone();
+        state.apply(ReplayInput::Move(Direction::Right));
+        let second = state.board.clone();
+
+        state.apply(ReplayInput::Undo);
+        assert_eq!(state.board, first);
+        state.apply(ReplayInput::Undo);
+        assert_eq!(state.board, start);
+
+        // Nothing left to undo
+        state.apply(ReplayInput::Undo);
+        assert_eq!((state.moves, state.undos), (0, 2));
+
+        state.apply(ReplayInput::Redo);
+        state.apply(ReplayInput::Redo);
+        assert_eq!(state.board, second);
+        assert_eq!((state.moves, state.undos), (2, 2));
+
+        // A new move clears the redo list
+        state.apply(ReplayInput::Undo);
+        state.apply(ReplayInput::Move(Direction::Down));
+        let moved = state.board.clone();
+        assert_ne!(moved, first);
+        state.apply(ReplayInput::Redo);
+        assert_eq!(state.board, moved);
+    }
+
+    #[test]
+    fn step_back_matches_step_forward() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut player = player(&pack);
+        let mut boards = vec![board(&player)];
+
+        while !player.is_finished() {
+            player.step_forward();
+            boards.push(board(&player));
+        }
+
+        // Both levels, with the start of the second level between them
+        assert_eq!(boards.len(), 8 + 1 + 8 + 1);
+        assert!(boards[8].is_solved());
+        assert_eq!(player.get_level_number(), 1);
+
+        for expected in boards.iter().rev().skip(1) {
+            player.step_back();
+            assert_eq!(&board(&player), expected);
+        }
+
+        assert_eq!(player.get_level_number(), 0);
+        assert_eq!(player.get_time(), 0.0);
+    }
+
+    #[test]
+    fn go_to_matches_stepping() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut stepped = player(&pack);
+        let mut jumped = player(&pack);
+
+        for level in 0..2 {
+            for applied in 1..=8 {
+                stepped.step_forward();
+                jumped.go_to(level, applied);
+
+                assert_eq!(board(&jumped), board(&stepped));
+                assert_eq!(jumped.get_state().moves, stepped.get_state().moves);
+                assert_eq!(jumped.get_time(), stepped.get_time());
+            }
+
+            stepped.step_forward();
+        }
+    }
+
+    #[test]
+    fn seek_matches_stepping() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut stepped = player(&pack);
+        let mut seeked = player(&pack);
+
+        // Into the second level
+        seeked.go_to(1, 0);
+        stepped.go_to(1, 0);
+
+        for _ in 0..5 {
+            stepped.step_forward();
+        }
+
+        seeked.seek(2600.0);
+        assert_eq!(board(&seeked), board(&stepped));
+        assert_eq!(seeked.get_time(), 2600.0);
+
+        // Back to the third input
+        for _ in 0..2 {
+            stepped.step_back();
+        }
+
+        seeked.seek(-1100.0);
+        assert_eq!(board(&seeked), board(&stepped));
+
+        // Seeking stays within the level
+        seeked.seek(-10_000.0);
+        assert_eq!(seeked.get_level_number(), 1);
+        assert_eq!(board(&seeked), pack.load_level(1).unwrap().board);
+
+        seeked.seek(10_000.0);
+        assert!(seeked.get_state().solved);
+        assert_eq!(seeked.get_time(), 4000.0);
+    }
+
+    #[test]
+    fn playback_moves_on_to_the_next_level() {
+        let pack = LevelPack::load(PACK_PATH).unwrap();
+        let mut player = player(&pack);
+
+        player.advance(4000.0);
+        assert!(player.get_state().solved);
+        assert_eq!(player.get_level_number(), 0);
+
+        player.advance(LEVEL_PAUSE_MS);
+        assert_eq!(player.get_level_number(), 1);
+        assert_eq!(board(&player), pack.load_level(1).unwrap().board);
+    }
+}
@@ -0,0 +1,256 @@
+// Rust modules
+use std::rc::Rc;
+
+// External modules
+use sdl2::event::Event;
+
+// Local modules
+use crate::render::{Renderer};
+use crate::game::{UPDATE_STEP};
+use crate::board_view::{BoardView};
+use crate::level_pack::{self, LevelPack};
+use crate::replay::{Replay, ReplayPlayer};
+use crate::screen::{Screen, ScreenContext};
+use crate::text_fx::{Font, StaticText, WaveVText};
+use crate::video::{LOGICAL_WIDTH, LOGICAL_HEIGHT};
+use crate::play_game;
+use crate::audio::{Sfx, Playlist};
+use crate::input::{Action};
+
+const CELL_SIZE: u32 = 32;
+// Playback speeds, Up / Down change between them
+const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const DEFAULT_SPEED: usize = 2;
+// Left / Right jump this far while playing, while paused they step a single input
+const SEEK_MS: f64 = 5000.0;
+
+// Plays back a replay from the high score table
+pub struct ReplayViewer {
+    player: Option<ReplayPlayer>,
+    board_view: BoardView,
+    paused: bool,
+    speed: usize,
+    title: WaveVText,
+    help: StaticText,
+    info: StaticText,
+    status: StaticText,
+    verified: StaticText,
+    message: StaticText,
+}
+
+impl ReplayViewer {
+    pub fn new() -> ReplayViewer {
+        ReplayViewer {
+            player: None,
+            board_view: BoardView::new(CELL_SIZE),
+            paused: false,
+            speed: DEFAULT_SPEED,
+            title: WaveVText::new(400, 20, 15.0, 0.1, 0.5, "REPLAY".to_string()),
+            help: StaticText::new(400, 60, "RETURN PAUSE  ARROWS SEEK/SPEED".to_string()),
+            info: StaticText::new(20, 515, String::new()),
+            status: StaticText::new(20, 545, String::new()),
+            verified: StaticText::new(20, 575, String::new()),
+            message: StaticText::new(400, 300, String::new()),
+        }
+    }
+
+    // The score is checked before the replay is shown
+    fn start(&mut self, replay: Replay) {
+        let pack = match LevelPack::load(level_pack::PACK_PATH) {
+            Ok(pack) => pack,
+            Err(e) => {
+                println!("Could not load level pack for replay: {}", e);
+                self.show_message("LEVELS NOT FOUND");
+                return
+            }
+        };
+
+        let verified = match replay.verify(&pack) {
+            Ok(score) => format!("SCORE {} VERIFIED", score),
+            Err(e) => {
+                println!("Replay could not be verified: {}", e);
+                format!("SCORE {} NOT VERIFIED", replay.score)
+            }
+        };
+        self.verified.set_text(verified);
+
+        self.title.set_text(format!("REPLAY - {}", replay.score));
+        self.title.set_x(400);
+        self.title.center();
+
+        match ReplayPlayer::new(&replay, &pack) {
+            Ok(player) => {
+                self.player = Some(player);
+            },
+            Err(e) => {
+                println!("Could not play replay: {}", e);
+                self.show_message("REPLAY IS BROKEN");
+            }
+        }
+    }
+
+    fn show_message(&mut self, text: &str) {
+        self.message.set_text(text.to_string());
+        self.message.set_x(400);
+        self.message.center();
+    }
+
+    fn change_speed(&mut self, step: i32) -> bool {
+        let speed = (self.speed as i32 + step).clamp(0, SPEEDS.len() as i32 - 1) as usize;
+        let changed = speed != self.speed;
+        self.speed = speed;
+        changed
+    }
+
+    fn update_text(&mut self) {
+        let player = match &self.player {
+            Some(player) => player,
+            None => {
+                return
+            }
+        };
+
+        let state = player.get_state();
+        self.info.set_text(format!("LEVEL {}  MOVES: {}  UNDOS: {}", player.get_level_number() + 1, state.moves, state.undos));
+
+        let status = if player.is_finished() {
+            "FINISHED".to_string()
+        } else if self.paused {
+            "PAUSED".to_string()
+        } else {
+            format!("PLAYING {}X", SPEEDS[self.speed])
+        };
+        self.status.set_text(format!("{}  TIME: {:.1}", status, player.get_time() / 1000.0));
+    }
+}
+
+impl Screen for ReplayViewer {
+    // Gets the replay picked in the high score table
+    fn enter(&mut self, context: &mut ScreenContext) {
+        self.player = None;
+        self.paused = false;
+        self.speed = DEFAULT_SPEED;
+        self.board_view.stop_animation();
+        self.message.set_text(String::new());
+        self.verified.set_text(String::new());
+
+        match context.replay.take() {
+            Some(replay) => {
+                self.start(replay);
+            },
+            None => {
+                self.show_message("NO REPLAY");
+            }
+        }
+
+        self.update_text();
+    }
+
+    fn process(&mut self, _event: &Event, action: Option<Action>, context: &mut ScreenContext) {
+        let action = match action {
+            Some(action) => action,
+            None => {
+                return
+            }
+        };
+
+        if action == Action::Back {
+            context.audio.play_sfx(Sfx::MenuBack);
+            context.screen.back();
+            return
+        }
+
+        let player = match &mut self.player {
+            Some(player) => player,
+            None => {
+                return
+            }
+        };
+
+        match action {
+            Action::Confirm | Action::Pause => {
+                self.paused = !self.paused;
+                context.audio.play_sfx(Sfx::MenuConfirm);
+            },
+            Action::Left if self.paused => {
+                player.step_back();
+                self.board_view.stop_animation();
+            },
+            Action::Right if self.paused => {
+                let events = player.step_forward();
+                self.board_view.start_animation(&events);
+            },
+            Action::Left => {
+                player.seek(-SEEK_MS);
+                self.board_view.stop_animation();
+            },
+            Action::Right => {
+                player.seek(SEEK_MS);
+                self.board_view.stop_animation();
+            },
+            Action::Up => {
+                let changed = self.change_speed(1);
+                context.audio.play_sfx(if changed { Sfx::ValueChange } else { Sfx::Error });
+            },
+            Action::Down => {
+                let changed = self.change_speed(-1);
+                context.audio.play_sfx(if changed { Sfx::ValueChange } else { Sfx::Error });
+            },
+            _ => {
+            }
+        }
+
+        self.update_text();
+    }
+
+    fn update(&mut self, _context: &mut ScreenContext) {
+        self.title.update();
+        self.board_view.update();
+
+        if let Some(player) = &mut self.player {
+            if !self.paused {
+                let ms = UPDATE_STEP.as_secs_f64() * 1000.0 * SPEEDS[self.speed];
+                let events = player.advance(ms);
+
+                if !events.is_empty() {
+                    self.board_view.start_animation(&events);
+                }
+            }
+        }
+
+        self.update_text();
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
+        self.title.draw(canvas, alpha);
+        self.help.draw(canvas);
+
+        if let Some(player) = &self.player {
+            let board = &player.get_state().board;
+            let x = (LOGICAL_WIDTH as i32 - self.board_view.get_width(board) as i32) / 2;
+            let y = (LOGICAL_HEIGHT as i32 - self.board_view.get_height(board) as i32) / 2;
+
+            self.board_view.draw(canvas, board, x, y, alpha);
+            self.info.draw(canvas);
+            self.status.draw(canvas);
+        }
+
+        self.verified.draw(canvas);
+        self.message.draw(canvas);
+    }
+
+    fn set_font(&mut self, font: &Rc<Font>) {
+        self.title.set_font(font);
+        self.title.center();
+        self.help.set_font(font);
+        self.help.center();
+        self.info.set_font(font);
+        self.status.set_font(font);
+        self.verified.set_font(font);
+        self.message.set_font(font);
+    }
+
+    fn playlist(&self) -> Playlist {
+        play_game::playlist()
+    }
+}
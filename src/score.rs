@@ -0,0 +1,75 @@
+// Rust modules
+
+// External modules
+
+// Local modules
+
+// Points for solving a level at all
+const BASE_POINTS: u32 = 500;
+// Extra points for each level number, later levels are harder
+const LEVEL_BONUS: u32 = 100;
+// Points for solving a level within par
+const PAR_POINTS: u32 = 500;
+// Lost for each move above par
+const MOVE_PENALTY: u32 = 25;
+// Points for a fast solution, one point less for each second
+const TIME_POINTS: u32 = 300;
+// Lost for each undo
+const UNDO_PENALTY: u32 = 10;
+
+// Everything that counts for the score of a single solved level
+#[derive(Debug, Clone, Copy)]
+pub struct LevelResult {
+    // Zero based index of the level in the pack
+    pub level: usize,
+    pub moves: u32,
+    pub par_moves: u32,
+    pub undos: u32,
+    pub seconds: u32,
+}
+
+impl LevelResult {
+    pub fn score(&self) -> u32 {
+        let level_bonus = LEVEL_BONUS * (self.level as u32 + 1);
+        let extra_moves = self.moves.saturating_sub(self.par_moves);
+        let move_points = PAR_POINTS.saturating_sub(MOVE_PENALTY * extra_moves);
+        let time_points = TIME_POINTS.saturating_sub(self.seconds);
+        let points = BASE_POINTS + level_bonus + move_points + time_points;
+
+        points.saturating_sub(UNDO_PENALTY * self.undos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(level: usize, moves: u32, par_moves: u32, undos: u32, seconds: u32) -> LevelResult {
+        LevelResult { level, moves, par_moves, undos, seconds }
+    }
+
+    #[test]
+    fn perfect_solution() {
+        assert_eq!(result(0, 10, 10, 0, 0).score(), 1400);
+        // Fewer moves than par give no extra points
+        assert_eq!(result(0, 8, 10, 0, 0).score(), 1400);
+    }
+
+    #[test]
+    fn later_levels_give_more_points() {
+        assert_eq!(result(4, 10, 10, 0, 0).score(), 1800);
+    }
+
+    #[test]
+    fn penalties() {
+        // 4 moves above par, 100 seconds and 3 undos
+        assert_eq!(result(2, 14, 10, 3, 100).score(), 500 + 300 + 400 + 200 - 30);
+    }
+
+    #[test]
+    fn points_never_drop_below_zero() {
+        // Move and time points run out, the level points still count
+        assert_eq!(result(0, 100, 10, 0, 1000).score(), 600);
+        assert_eq!(result(0, 100, 10, 100, 1000).score(), 0);
+    }
+}
@@ -0,0 +1,109 @@
+// Rust modules
+use std::rc::Rc;
+use std::collections::HashMap;
+
+// External modules
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::event::Event;
+
+// Local modules
+use crate::game::{GameScreen, GameScreenKind};
+use crate::settings::{GameSettings};
+use crate::high_score::{HighScore};
+use crate::text_fx::{Font};
+use crate::render::{Renderer};
+use crate::replay::{Replay};
+use crate::audio::{Audio, Playlist};
+use crate::input::{Action, Device};
+
+// The parts of the game a screen may use or change
+pub struct ScreenContext<'a> {
+    pub screen: &'a mut GameScreen,
+    pub settings: &'a mut GameSettings,
+    pub audio: &'a mut Audio,
+    pub canvas: &'a mut Canvas<Window>,
+    pub high_score: &'a mut HighScore,
+    // Score and replay of the run that has just ended, waiting for the name of the player
+    pub finished_run: &'a mut Option<(u32, Replay)>,
+    // The replay the replay viewer shows next
+    pub replay: &'a mut Option<Replay>,
+    // The device the player used last
+    pub device: Device,
+    pub fps: u32,
+    pub quit: &'a mut bool,
+    // A headless game, e.g. running a script, does not write any files
+    pub headless: bool,
+}
+
+// A menu, the level, the editor...
+// Only the screen on top of the stack gets the events and is updated.
+pub trait Screen {
+    // Called when the screen is opened, not when a screen on top of it is closed
+    fn enter(&mut self, _context: &mut ScreenContext) {
+    }
+
+    // Called when the screen is closed
+    fn exit(&mut self, _context: &mut ScreenContext) {
+    }
+
+    // Called for every open screen when the game quits, before the settings are saved
+    fn quit(&mut self, _context: &mut ScreenContext) {
+    }
+
+    // Gets every event together with its action, if there is one
+    fn process(&mut self, event: &Event, action: Option<Action>, context: &mut ScreenContext);
+
+    fn update(&mut self, context: &mut ScreenContext);
+
+    // Alpha is the time since the last update in updates (0.0..1.0),
+    // animations use it to move smoothly between two updates
+    fn draw(&self, canvas: &mut dyn Renderer, alpha: f64);
+
+    fn set_font(&mut self, font: &Rc<Font>);
+
+    // Index of the selected menu item, scripts check it
+    fn selected_item(&self) -> Option<usize> {
+        None
+    }
+
+    // An overlay like the pause menu is drawn on top of the screen below it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    fn playlist(&self) -> Playlist {
+        Playlist::new(&["menu"], false)
+    }
+}
+
+// All screens of the game, a new screen only has to be registered here
+pub struct ScreenRegistry {
+    screens: HashMap<GameScreenKind, Box<dyn Screen>>,
+}
+
+impl ScreenRegistry {
+    pub fn new() -> ScreenRegistry {
+        ScreenRegistry {
+            screens: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, kind: GameScreenKind, screen: Box<dyn Screen>) {
+        self.screens.insert(kind, screen);
+    }
+
+    pub fn get(&self, kind: GameScreenKind) -> &dyn Screen {
+        self.screens.get(&kind).expect("Screen is not registered").as_ref()
+    }
+
+    pub fn get_mut(&mut self, kind: GameScreenKind) -> &mut dyn Screen {
+        self.screens.get_mut(&kind).expect("Screen is not registered").as_mut()
+    }
+
+    pub fn set_font(&mut self, font: &Rc<Font>) {
+        for screen in self.screens.values_mut() {
+            screen.set_font(font);
+        }
+    }
+}
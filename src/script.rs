@@ -0,0 +1,266 @@
+// Rust modules
+use std::fs;
+use std::error;
+use std::fmt;
+use std::path::Path;
+use std::io::Error as StdIOError;
+
+// External modules
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use serde_derive::{Serialize, Deserialize};
+use serde_json::error::Error as JSONError;
+use serde_json::{Map, Value};
+
+// Local modules
+use crate::game::{Game, GameScreenKind};
+use crate::event_source::{ScriptedEvents};
+
+// One line of a script, everything in it happens in the given frame.
+// The input is processed first, then the expectations are checked.
+//
+// { "frame": 0, "key": "Down" }
+// { "frame": 4, "key": "Return", "expect_screen": "GFXMenu", "expect_selected": 0 }
+// { "frame": 6, "key": "Right", "expect_settings": { "fullscreen": true } }
+// { "frame": 8, "quit": true, "expect_settings": { "fullscreen": false } }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScriptStep {
+    pub frame: u32,
+    // SDL key name, e.g. "Return", "Escape" or "A"
+    pub key: Option<String>,
+    // Left mouse click at a logical position
+    pub click: Option<(i32, i32)>,
+    // Closes the window, the expectations are checked after the game has shut down
+    #[serde(default)]
+    pub quit: bool,
+    pub expect_screen: Option<GameScreenKind>,
+    // Index of the selected item of the menu on top
+    pub expect_selected: Option<usize>,
+    // Settings as they are stored in the settings file, only the given ones are compared
+    pub expect_settings: Option<Map<String, Value>>,
+}
+
+// Input for the game and what the game should look like afterwards
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Script {
+    pub steps: Vec<ScriptStep>,
+}
+
+impl Script {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Script, ScriptError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| ScriptError::ReadError(e, path.display().to_string()))?;
+        let script: Script = serde_json::from_str(&data)?;
+
+        Ok(script)
+    }
+
+    // The events of all steps, unknown key names are an error
+    pub fn to_events(&self) -> Result<ScriptedEvents, ScriptError> {
+        let mut events = ScriptedEvents::new();
+
+        for step in self.steps.iter() {
+            if let Some(ref name) = step.key {
+                let keycode = Keycode::from_name(name)
+                    .ok_or_else(|| ScriptError::UnknownKey(name.clone(), step.frame))?;
+                events.push_key(step.frame, keycode);
+            }
+
+            if let Some((x, y)) = step.click {
+                events.push_click(step.frame, x, y);
+            }
+
+            if step.quit {
+                events.push(step.frame, Event::Quit { timestamp: 0 });
+            }
+        }
+
+        Ok(events)
+    }
+
+    pub fn last_frame(&self) -> u32 {
+        self.steps.iter().map(|step| step.frame).max().unwrap_or(0)
+    }
+}
+
+// Runs the script in a headless game and prints a report.
+// Returns false if the script could not be run or any expectation failed.
+pub fn run_script<T: AsRef<Path>>(path: T) -> bool {
+    let path = path.as_ref();
+
+    let script = match Script::load(path) {
+        Ok(script) => script,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return false
+        }
+    };
+
+    let events = match script.to_events() {
+        Ok(events) => events,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return false
+        }
+    };
+
+    let mut game = match Game::new_headless() {
+        Ok(game) => game,
+        Err(e) => {
+            println!("ERROR: could not create headless game: {}", e);
+            return false
+        }
+    };
+
+    if let Err(e) = game.load_resources() {
+        println!("ERROR: {}", e);
+        return false
+    }
+
+    game.set_event_source(Box::new(events));
+
+    println!("Script: '{}', {} steps, {} frames", path.display(), script.steps.len(), script.last_frame() + 1);
+
+    let mut valid = true;
+
+    for frame in 0..=script.last_frame() {
+        game.step();
+
+        if game.is_quit() {
+            game.shut_down();
+        }
+
+        for step in script.steps.iter().filter(|step| step.frame == frame) {
+            valid &= check_step(&game, step);
+        }
+
+        if game.is_quit() {
+            println!("{:5}: game has quit", frame);
+            break
+        }
+    }
+
+    valid
+}
+
+// Prints every expectation of the step, returns false if one of them failed
+fn check_step(game: &Game, step: &ScriptStep) -> bool {
+    let frame = step.frame;
+    let mut valid = true;
+
+    if let Some(expected) = step.expect_screen {
+        let screen = game.current_screen();
+
+        if screen == expected {
+            println!("{:5}: screen is {:?}", frame, screen);
+        } else {
+            println!("{:5}: FAILED: screen is {:?}, expected {:?}", frame, screen, expected);
+            valid = false;
+        }
+    }
+
+    if let Some(expected) = step.expect_selected {
+        let selected = game.selected_item();
+
+        if selected == Some(expected) {
+            println!("{:5}: item {} is selected", frame, expected);
+        } else {
+            println!("{:5}: FAILED: selected item is {:?}, expected {}", frame, selected, expected);
+            valid = false;
+        }
+    }
+
+    if let Some(ref expected) = step.expect_settings {
+        let settings = serde_json::to_value(game.settings()).unwrap_or(Value::Null);
+
+        for (name, value) in expected.iter() {
+            match settings.get(name) {
+                Some(actual) if actual == value => {
+                    println!("{:5}: setting {} is {}", frame, name, value);
+                },
+                Some(actual) => {
+                    println!("{:5}: FAILED: setting {} is {}, expected {}", frame, name, actual, value);
+                    valid = false;
+                },
+                None => {
+                    println!("{:5}: FAILED: unknown setting {}", frame, name);
+                    valid = false;
+                }
+            }
+        }
+    }
+
+    valid
+}
+
+#[derive(Debug)]
+pub enum ScriptError {
+    ReadError(StdIOError, String),
+    ParseError(JSONError),
+    UnknownKey(String, u32),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScriptError::ReadError(ref e, ref path) => {
+                write!(f, "IO error while reading the script: '{}', {}", path, e)
+            },
+            ScriptError::ParseError(ref e) => {
+                write!(f, "Parse error while reading the script: {}", e)
+            },
+            ScriptError::UnknownKey(ref name, frame) => {
+                write!(f, "Unknown key '{}' in frame {}", name, frame)
+            },
+        }
+    }
+}
+
+impl error::Error for ScriptError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ScriptError::ReadError(ref e, _) => {
+                Some(e)
+            },
+            ScriptError::ParseError(ref e) => {
+                Some(e)
+            },
+            _ => {
+                None
+            },
+        }
+    }
+}
+
+impl From<JSONError> for ScriptError {
+    fn from(e: JSONError) -> ScriptError {
+        ScriptError::ParseError(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{SDL_LOCK};
+
+    const SCRIPT_DIR: &str = "assets/scripts";
+
+    // Every script in the scripts folder, they need the game assets
+    #[test]
+    fn scripts() {
+        let _lock = SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut paths: Vec<_> = fs::read_dir(SCRIPT_DIR).unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+
+        assert!(!paths.is_empty());
+
+        for path in paths.iter() {
+            assert!(run_script(path), "script '{}' failed, see the output above", path.display());
+        }
+    }
+}
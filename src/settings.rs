@@ -9,8 +9,56 @@ use std::io::Error as StdIOError;
 use serde_derive::{Serialize, Deserialize};
 use serde_json::error::Error as JSONError;
 
+// Local modules
+use crate::key_bindings::{KeyBindings};
+
 const MAX_VOLUME: i16 = 255;
-const MAX_RESOLUTION: i16 = 3;
+// Choices for the frame rate limit, 0 is unlimited
+const TARGET_FPS_STEPS: [u32; 5] = [30, 60, 120, 144, 0];
+const DEFAULT_TARGET_FPS: u32 = 60;
+
+// Window size or display mode for exclusive fullscreen.
+// A refresh rate of 0 lets SDL pick one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: i32,
+}
+
+impl ScreenMode {
+    pub fn new(width: u32, height: u32, refresh_rate: i32) -> ScreenMode {
+        ScreenMode {
+            width,
+            height,
+            refresh_rate,
+        }
+    }
+
+    pub fn to_text(self) -> String {
+        // The font has no lower case letters
+        if self.refresh_rate > 0 {
+            format!("{}X{} {}HZ", self.width, self.height, self.refresh_rate)
+        } else {
+            format!("{}X{}", self.width, self.height)
+        }
+    }
+}
+
+impl Default for ScreenMode {
+    fn default() -> ScreenMode {
+        ScreenMode::new(800, 600, 0)
+    }
+}
+
+// The part of the settings that defines the window / display mode.
+// Used to revert to the previous mode if a new one is not confirmed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoSettings {
+    pub fullscreen: bool,
+    pub exclusive_fullscreen: bool,
+    pub screen_mode: ScreenMode,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GameSettings {
@@ -18,8 +66,25 @@ pub struct GameSettings {
     sound_volume: i16,
     music_volume: i16,
     fullscreen: bool,
-    resolution: i16,
+    // Use a real display mode change instead of the desktop resolution
+    #[serde(default)]
+    exclusive_fullscreen: bool,
+    #[serde(default)]
+    screen_mode: ScreenMode,
+    // Only scale the logical canvas by whole numbers, keeps the pixels sharp
+    #[serde(default)]
+    integer_scaling: bool,
+    // Wait for the display refresh, only used when the game starts
+    #[serde(default)]
+    vsync: bool,
+    // Frame rate limit without vsync, 0 is unlimited
+    #[serde(default = "default_target_fps")]
+    target_fps: u32,
     filepath: String,
+    #[serde(default)]
+    solved_levels: Vec<String>,
+    #[serde(default)]
+    key_bindings: KeyBindings,
 }
 
 impl GameSettings {
@@ -29,8 +94,14 @@ impl GameSettings {
             sound_volume: 200,
             music_volume: 200,
             fullscreen: false,
-            resolution: 0,
+            exclusive_fullscreen: false,
+            screen_mode: ScreenMode::default(),
+            integer_scaling: false,
+            vsync: false,
+            target_fps: DEFAULT_TARGET_FPS,
             filepath: "assets/settings.json".to_string(),
+            solved_levels: Vec::new(),
+            key_bindings: KeyBindings::default(),
         }
     }
 
@@ -50,6 +121,24 @@ impl GameSettings {
         Ok(())
     }
 
+    pub fn get_start_level(&self) -> u8 {
+        self.start_level
+    }
+
+    pub fn set_start_level(&mut self, level: u8) {
+        self.start_level = level;
+    }
+
+    pub fn is_level_solved(&self, level: &str) -> bool {
+        self.solved_levels.iter().any(|l| l == level)
+    }
+
+    pub fn set_level_solved(&mut self, level: &str) {
+        if !self.is_level_solved(level) {
+            self.solved_levels.push(level.to_string());
+        }
+    }
+
     pub fn inc_sound_vol(&mut self) {
         self.sound_volume += 5;
         limit_range(&mut self.sound_volume, 0, MAX_VOLUME);
@@ -78,39 +167,120 @@ impl GameSettings {
         self.music_volume
     }
 
+    // Cycles through: window, desktop fullscreen, exclusive fullscreen
     pub fn toggle_fullscreen(&mut self) {
-        self.fullscreen = !self.fullscreen;
+        if !self.fullscreen {
+            self.fullscreen = true;
+            self.exclusive_fullscreen = false;
+        } else if !self.exclusive_fullscreen {
+            self.exclusive_fullscreen = true;
+        } else {
+            self.fullscreen = false;
+            self.exclusive_fullscreen = false;
+        }
     }
 
     pub fn get_fullscreen(&self) -> bool {
         self.fullscreen
     }
 
-    pub fn inc_resolution(&mut self) {
-        self.resolution += 1;
-        limit_range(&mut self.resolution, 0, MAX_RESOLUTION);
+    pub fn get_exclusive_fullscreen(&self) -> bool {
+        self.exclusive_fullscreen
+    }
+
+    pub fn fullscreen_to_text(&self) -> String {
+        match (self.fullscreen, self.exclusive_fullscreen) {
+            (false, _) => "OFF".to_string(),
+            (true, false) => "DESKTOP".to_string(),
+            (true, true) => "EXCLUSIVE".to_string(),
+        }
+    }
+
+    pub fn get_video(&self) -> VideoSettings {
+        VideoSettings {
+            fullscreen: self.fullscreen,
+            exclusive_fullscreen: self.exclusive_fullscreen,
+            screen_mode: self.screen_mode,
+        }
+    }
+
+    pub fn set_video(&mut self, video: VideoSettings) {
+        self.fullscreen = video.fullscreen;
+        self.exclusive_fullscreen = video.exclusive_fullscreen;
+        self.screen_mode = video.screen_mode;
     }
 
-    pub fn dec_resolution(&mut self) {
-        self.resolution -= 1;
-        limit_range(&mut self.resolution, 0, MAX_RESOLUTION);
+    pub fn toggle_integer_scaling(&mut self) {
+        self.integer_scaling = !self.integer_scaling;
     }
 
-    pub fn get_resolution(&self) -> i16 {
-        self.resolution
+    pub fn get_integer_scaling(&self) -> bool {
+        self.integer_scaling
     }
 
-    pub fn resolution_to_text(&self) -> String {
-        match self.resolution {
-            0 => "800x600".to_string(),
-            1 => "1024x768".to_string(),
-            2 => "1280x1024".to_string(),
-            3 => "1980x1280".to_string(),
-            _ => {
-                unimplemented!();
-            }
+    pub fn scaling_to_text(&self) -> String {
+        if self.integer_scaling {
+            "INTEGER".to_string()
+        } else {
+            "SMOOTH".to_string()
         }
     }
+
+    pub fn toggle_vsync(&mut self) {
+        self.vsync = !self.vsync;
+    }
+
+    pub fn get_vsync(&self) -> bool {
+        self.vsync
+    }
+
+    pub fn vsync_to_text(&self) -> String {
+        if self.vsync {
+            "ON".to_string()
+        } else {
+            "OFF".to_string()
+        }
+    }
+
+    // Selects the next lower (-1) or higher (1) limit, returns false at the ends of the list
+    pub fn change_target_fps(&mut self, step: i32) -> bool {
+        let index = TARGET_FPS_STEPS.iter().position(|fps| *fps == self.target_fps)
+            .or_else(|| TARGET_FPS_STEPS.iter().position(|fps| *fps == DEFAULT_TARGET_FPS))
+            .unwrap() as i32;
+        let new_index = (index + step).clamp(0, TARGET_FPS_STEPS.len() as i32 - 1) as usize;
+        let changed = TARGET_FPS_STEPS[new_index] != self.target_fps;
+
+        self.target_fps = TARGET_FPS_STEPS[new_index];
+        changed
+    }
+
+    pub fn get_target_fps(&self) -> u32 {
+        self.target_fps
+    }
+
+    pub fn target_fps_to_text(&self) -> String {
+        if self.target_fps == 0 {
+            "UNLIMITED".to_string()
+        } else {
+            self.target_fps.to_string()
+        }
+    }
+
+    pub fn get_key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
+    }
+
+    pub fn get_key_bindings_mut(&mut self) -> &mut KeyBindings {
+        &mut self.key_bindings
+    }
+
+    pub fn get_screen_mode(&self) -> ScreenMode {
+        self.screen_mode
+    }
+
+    pub fn set_screen_mode(&mut self, screen_mode: ScreenMode) {
+        self.screen_mode = screen_mode;
+    }
 }
 
 #[derive(Debug)]
@@ -165,3 +335,7 @@ fn limit_range(value: &mut i16, low: i16, high: i16) {
         *value = high;
     }
 }
+
+fn default_target_fps() -> u32 {
+    DEFAULT_TARGET_FPS
+}
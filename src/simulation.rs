@@ -0,0 +1,404 @@
+// Rust modules
+
+// External modules
+
+// Local modules
+use crate::board::{Board, Tile, Piece, Polarity, Direction, Position};
+
+// The magnet rules, independent from any rendering:
+//
+// 1. The player walks one step and pushes a single piece if there is room behind it.
+// 2. Every magnet (in row major order) looks along its row and column.
+//    The first piece in line of sight is:
+//    - pulled next to the magnet if it is a block or a magnet with opposite polarity,
+//    - pushed away until it hits something if it is a magnet with the same polarity.
+//    Walls and the player block the line of sight.
+// 3. Two opposite magnets that touch snap together and merge into a single block.
+// 4. Step 2 and 3 are repeated until nothing moves anymore (chain reaction).
+
+// Maximum number of passes for the magnet chain reaction.
+// Prevents endless loops in case of oscillating magnets.
+const MAX_MAGNET_PASSES: u32 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardEvent {
+    // The player walked from the first to the second position
+    PlayerMoved(Position, Position),
+    // A piece was pushed or pulled from the first to the second position
+    PieceMoved(Piece, Position, Position),
+    // The player could not move, the board did not change
+    Stuck(Position),
+    // The magnet at the first position merged with the magnet at the second position
+    Merged(Position, Position),
+    // A block came to rest on the goal at this position
+    GoalReached(Position),
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveResult {
+    pub board: Board,
+    pub events: Vec<BoardEvent>,
+}
+
+impl MoveResult {
+    pub fn has_moved(&self) -> bool {
+        self.events.iter().any(|e| matches!(e, BoardEvent::PlayerMoved(_, _)))
+    }
+}
+
+// Applies a single player move and returns the new board state and everything that happened.
+pub fn apply_move(board: &Board, direction: Direction) -> MoveResult {
+    let mut new_board = board.clone();
+    let mut events = Vec::new();
+
+    let start = board.get_player();
+
+    let next = match board.neighbour(start, direction) {
+        Some(pos) if board.get_tile(pos) != Tile::Wall => pos,
+        _ => {
+            events.push(BoardEvent::Stuck(start));
+            return MoveResult { board: new_board, events }
+        }
+    };
+
+    if let Some(piece) = board.get_piece(next) {
+        match board.neighbour(next, direction) {
+            Some(behind) if board.is_free(behind) => {
+                new_board.set_piece(next, None);
+                new_board.set_piece(behind, Some(piece));
+                events.push(BoardEvent::PieceMoved(piece, next, behind));
+            },
+            _ => {
+                events.push(BoardEvent::Stuck(start));
+                return MoveResult { board: new_board, events }
+            }
+        }
+    }
+
+    new_board.set_player(next);
+    events.insert(0, BoardEvent::PlayerMoved(start, next));
+
+    events.append(&mut apply_magnets(&mut new_board));
+
+    for y in 0..new_board.get_height() {
+        for x in 0..new_board.get_width() {
+            let pos = (x, y);
+            if goal_filled(&new_board, pos) && !goal_filled(board, pos) {
+                events.push(BoardEvent::GoalReached(pos));
+            }
+        }
+    }
+
+    MoveResult { board: new_board, events }
+}
+
+// Let all magnets act until nothing moves anymore.
+// Used after each move and to bring a freshly loaded level into a stable state.
+pub fn apply_magnets(board: &mut Board) -> Vec<BoardEvent> {
+    let mut events = Vec::new();
+
+    for _ in 0..MAX_MAGNET_PASSES {
+        if !magnet_pass(board, &mut events) {
+            break
+        }
+    }
+
+    events
+}
+
+fn goal_filled(board: &Board, pos: Position) -> bool {
+    board.get_tile(pos) == Tile::Goal && board.get_piece(pos) == Some(Piece::Block)
+}
+
+fn magnet_pass(board: &mut Board, events: &mut Vec<BoardEvent>) -> bool {
+    let mut changed = false;
+
+    for y in 0..board.get_height() {
+        for x in 0..board.get_width() {
+            for direction in Direction::all().iter() {
+                // The magnet may have been merged into a block by the previous direction
+                if let Some(Piece::Magnet(polarity)) = board.get_piece((x, y)) {
+                    changed |= magnet_act(board, (x, y), polarity, *direction, events);
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+fn magnet_act(board: &mut Board, magnet: Position, polarity: Polarity, direction: Direction, events: &mut Vec<BoardEvent>) -> bool {
+    // Find the first thing in line of sight
+    let mut pos = board.neighbour(magnet, direction);
+
+    while let Some(current) = pos {
+        if !board.is_free(current) {
+            break
+        }
+        pos = board.neighbour(current, direction);
+    }
+
+    let found = match pos {
+        Some(found) => found,
+        None => return false,
+    };
+
+    let piece = match board.get_piece(found) {
+        Some(piece) => piece,
+        // Wall or player
+        None => return false,
+    };
+
+    let attract = match piece {
+        Piece::Block => true,
+        Piece::Magnet(other) => other != polarity,
+    };
+
+    if attract {
+        // Pull the piece next to the magnet
+        let mut changed = false;
+        let mut target = found;
+
+        if let Some(first) = board.neighbour(magnet, direction) {
+            if first != found {
+                board.set_piece(found, None);
+                board.set_piece(first, Some(piece));
+                events.push(BoardEvent::PieceMoved(piece, found, first));
+                target = first;
+                changed = true;
+            }
+        }
+
+        if let Piece::Magnet(_) = piece {
+            board.set_piece(target, None);
+            board.set_piece(magnet, Some(Piece::Block));
+            events.push(BoardEvent::Merged(target, magnet));
+            changed = true;
+        }
+
+        changed
+    } else {
+        // Push the piece away until it hits something
+        let mut target = found;
+
+        while let Some(next) = board.neighbour(target, direction) {
+            if !board.is_free(next) {
+                break
+            }
+            target = next;
+        }
+
+        if target == found {
+            false
+        } else {
+            board.set_piece(found, None);
+            board.set_piece(target, Some(piece));
+            events.push(BoardEvent::PieceMoved(piece, found, target));
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Same characters as the level files, but the magnets do not act on creation
+    fn board(rows: &[&str]) -> Board {
+        let mut board = Board::new(rows[0].len() as u32, rows.len() as u32);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let pos = (x as u32, y as u32);
+
+                match c {
+                    '#' => board.set_tile(pos, Tile::Wall),
+                    '.' => board.set_tile(pos, Tile::Goal),
+                    'B' => board.set_piece(pos, Some(Piece::Block)),
+                    'N' => board.set_piece(pos, Some(Piece::Magnet(Polarity::North))),
+                    'S' => board.set_piece(pos, Some(Piece::Magnet(Polarity::South))),
+                    '@' => board.set_player(pos),
+                    _ => {},
+                }
+            }
+        }
+
+        board
+    }
+
+    const BLOCK: Option<Piece> = Some(Piece::Block);
+    const NORTH: Option<Piece> = Some(Piece::Magnet(Polarity::North));
+    const SOUTH: Option<Piece> = Some(Piece::Magnet(Polarity::South));
+
+    #[test]
+    fn attraction_along_row() {
+        let mut board = board(&["#@N   B#"]);
+        let events = apply_magnets(&mut board);
+
+        assert_eq!(board.get_piece((6, 0)), None);
+        assert_eq!(board.get_piece((3, 0)), BLOCK);
+        assert_eq!(events, vec![BoardEvent::PieceMoved(Piece::Block, (6, 0), (3, 0))]);
+    }
+
+    #[test]
+    fn attraction_along_column() {
+        let mut board = board(&["###", "#S#", "# #", "# #", "#B#", "#@#"]);
+        apply_magnets(&mut board);
+
+        assert_eq!(board.get_piece((1, 4)), None);
+        assert_eq!(board.get_piece((1, 2)), BLOCK);
+    }
+
+    #[test]
+    fn repulsion_along_row() {
+        let mut board = board(&["#N N  #", "#@    #"]);
+        let events = apply_magnets(&mut board);
+
+        assert_eq!(board.get_piece((1, 0)), NORTH);
+        assert_eq!(board.get_piece((3, 0)), None);
+        assert_eq!(board.get_piece((5, 0)), NORTH);
+        assert_eq!(events, vec![BoardEvent::PieceMoved(Piece::Magnet(Polarity::North), (3, 0), (5, 0))]);
+    }
+
+    #[test]
+    fn repulsion_along_column() {
+        let mut board = board(&["###", "#S#", "#S#", "# #", "# #", "###", "#@#"]);
+        apply_magnets(&mut board);
+
+        assert_eq!(board.get_piece((1, 1)), SOUTH);
+        assert_eq!(board.get_piece((1, 2)), None);
+        assert_eq!(board.get_piece((1, 4)), SOUTH);
+    }
+
+    #[test]
+    fn walls_and_player_block_line_of_sight() {
+        let mut walled = board(&["#N#  B#", "#@    #"]);
+        let mut behind_player = board(&["#N @ B#"]);
+
+        assert!(apply_magnets(&mut walled).is_empty());
+        assert!(apply_magnets(&mut behind_player).is_empty());
+    }
+
+    #[test]
+    fn repelled_piece_slides_until_stopped() {
+        let mut board = board(&["#S S     B #", "#@         #"]);
+        apply_magnets(&mut board);
+
+        // Stopped by the block, not by the wall
+        assert_eq!(board.get_piece((3, 0)), None);
+        assert_eq!(board.get_piece((8, 0)), SOUTH);
+        assert_eq!(board.get_piece((9, 0)), BLOCK);
+    }
+
+    #[test]
+    fn opposite_magnets_merge() {
+        let mut board = board(&["#N   S#", "#@    #"]);
+        let events = apply_magnets(&mut board);
+
+        assert_eq!(board.get_piece((1, 0)), BLOCK);
+        assert_eq!(board.get_piece((2, 0)), None);
+        assert_eq!(board.get_piece((5, 0)), None);
+        assert_eq!(events, vec![
+            BoardEvent::PieceMoved(Piece::Magnet(Polarity::South), (5, 0), (2, 0)),
+            BoardEvent::Merged((2, 0), (1, 0)),
+        ]);
+    }
+
+    #[test]
+    fn touching_opposite_magnets_merge() {
+        let mut board = board(&["#SN#", "#@ #"]);
+        let events = apply_magnets(&mut board);
+
+        assert_eq!(board.get_piece((1, 0)), BLOCK);
+        assert_eq!(board.get_piece((2, 0)), None);
+        assert_eq!(events, vec![BoardEvent::Merged((2, 0), (1, 0))]);
+    }
+
+    #[test]
+    fn chain_reaction() {
+        // The player clears the way, the magnets merge and the new block is pulled down
+        let start = board(&[
+            "#######",
+            "#N@  S#",
+            "#     #",
+            "#N    #",
+            "#######",
+        ]);
+        let result = apply_move(&start, Direction::Down);
+
+        assert_eq!(result.events, vec![
+            BoardEvent::PlayerMoved((2, 1), (2, 2)),
+            BoardEvent::PieceMoved(Piece::Magnet(Polarity::South), (5, 1), (2, 1)),
+            BoardEvent::Merged((2, 1), (1, 1)),
+            BoardEvent::PieceMoved(Piece::Block, (1, 1), (1, 2)),
+        ]);
+        assert_eq!(result.board.get_piece((1, 2)), BLOCK);
+        assert_eq!(result.board.get_piece((1, 3)), NORTH);
+        assert_eq!(result.board.get_piece((1, 1)), None);
+        assert_eq!(result.board.get_piece((5, 1)), None);
+    }
+
+    #[test]
+    fn player_walks() {
+        let start = board(&["#@  #"]);
+        let result = apply_move(&start, Direction::Right);
+
+        assert!(result.has_moved());
+        assert_eq!(result.board.get_player(), (2, 0));
+        assert_eq!(result.events, vec![BoardEvent::PlayerMoved((1, 0), (2, 0))]);
+    }
+
+    #[test]
+    fn player_pushes_a_piece() {
+        let start = board(&["#@B  #"]);
+        let result = apply_move(&start, Direction::Right);
+
+        assert_eq!(result.board.get_player(), (2, 0));
+        assert_eq!(result.board.get_piece((3, 0)), BLOCK);
+        assert_eq!(result.events, vec![
+            BoardEvent::PlayerMoved((1, 0), (2, 0)),
+            BoardEvent::PieceMoved(Piece::Block, (2, 0), (3, 0)),
+        ]);
+    }
+
+    #[test]
+    fn player_is_stuck() {
+        let cases = [
+            (board(&["#@#"]), Direction::Right),
+            (board(&["#@BB #"]), Direction::Right),
+            (board(&["#@B#"]), Direction::Right),
+            (board(&["@  "]), Direction::Left),
+        ];
+
+        for (start, direction) in cases.iter() {
+            let result = apply_move(start, *direction);
+
+            assert!(!result.has_moved());
+            assert_eq!(&result.board, start);
+            assert_eq!(result.events, vec![BoardEvent::Stuck(start.get_player())]);
+        }
+    }
+
+    #[test]
+    fn goal_reached() {
+        let start = board(&["#@B.#"]);
+        let result = apply_move(&start, Direction::Right);
+
+        assert!(result.board.is_solved());
+        assert_eq!(result.events, vec![
+            BoardEvent::PlayerMoved((1, 0), (2, 0)),
+            BoardEvent::PieceMoved(Piece::Block, (2, 0), (3, 0)),
+            BoardEvent::GoalReached((3, 0)),
+        ]);
+    }
+
+    #[test]
+    fn goal_already_filled_is_not_reported_again() {
+        let mut start = board(&["#@ #", "#  #", "#B #"]);
+        start.set_tile((1, 2), Tile::Goal);
+        let result = apply_move(&start, Direction::Right);
+
+        assert!(result.has_moved());
+        assert!(!result.events.iter().any(|e| matches!(e, BoardEvent::GoalReached(_))));
+    }
+}
@@ -0,0 +1,137 @@
+// Rust modules
+use std::collections::{HashSet, VecDeque};
+
+// External modules
+
+// Local modules
+use crate::board::{Board, Direction};
+use crate::simulation;
+
+// Default number of board states the solver may visit before giving up
+pub const MAX_STATES: usize = 1_000_000;
+
+#[derive(Debug)]
+pub enum SolverResult {
+    // Shortest sequence of moves that solves the level
+    Solved(Vec<Direction>),
+    // Every reachable board state has been checked, there is no solution
+    Unsolvable(usize),
+    // The solver gave up after visiting that many board states
+    LimitReached(usize),
+}
+
+struct Node {
+    board: Board,
+    parent: usize,
+    direction: Direction,
+}
+
+// Breadth first search over all board states reachable from the given board.
+// Each move costs the same, so the first solution found is a shortest one.
+pub fn solve(board: &Board, max_states: usize) -> SolverResult {
+    if board.is_solved() {
+        return SolverResult::Solved(Vec::new())
+    }
+
+    let mut nodes = vec![Node {
+        board: board.clone(),
+        parent: 0,
+        direction: Direction::Left,
+    }];
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    visited.insert(board.clone());
+    queue.push_back(0);
+
+    while let Some(index) = queue.pop_front() {
+        for direction in Direction::all().iter() {
+            let result = simulation::apply_move(&nodes[index].board, *direction);
+
+            if !result.has_moved() || visited.contains(&result.board) {
+                continue
+            }
+
+            if visited.len() >= max_states {
+                return SolverResult::LimitReached(visited.len())
+            }
+
+            let solved = result.board.is_solved();
+            visited.insert(result.board.clone());
+            nodes.push(Node {
+                board: result.board,
+                parent: index,
+                direction: *direction,
+            });
+
+            if solved {
+                return SolverResult::Solved(path_to(&nodes, nodes.len() - 1))
+            }
+
+            queue.push_back(nodes.len() - 1);
+        }
+    }
+
+    SolverResult::Unsolvable(visited.len())
+}
+
+fn path_to(nodes: &[Node], mut index: usize) -> Vec<Direction> {
+    let mut path = Vec::new();
+
+    while index != 0 {
+        path.push(nodes[index].direction);
+        index = nodes[index].parent;
+    }
+
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::{Level};
+
+    fn board(grid: &str) -> Board {
+        Level::from_ascii(&format!("; version: 1\n{}", grid)).unwrap().board
+    }
+
+    #[test]
+    fn shortest_solution() {
+        // The detour through the lower row takes more moves
+        let start = board("#######\n#@ B .#\n#     #\n#######\n");
+
+        match solve(&start, MAX_STATES) {
+            SolverResult::Solved(path) => {
+                assert_eq!(path, vec![Direction::Right, Direction::Right, Direction::Right]);
+
+                let end = path.iter().fold(start, |board, direction| simulation::apply_move(&board, *direction).board);
+                assert!(end.is_solved());
+            },
+            result => panic!("expected a solution, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn already_solved() {
+        assert!(matches!(solve(&board("#@*#\n"), MAX_STATES), SolverResult::Solved(ref path) if path.is_empty()));
+    }
+
+    #[test]
+    fn unsolvable() {
+        // The block is stuck in the corner
+        let start = board("#####\n#@ .#\n#  B#\n#####\n");
+
+        match solve(&start, MAX_STATES) {
+            SolverResult::Unsolvable(states) => assert_eq!(states, 5),
+            result => panic!("expected no solution, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn limit_reached() {
+        let start = board("#######\n#@ B .#\n#     #\n#######\n");
+
+        assert!(matches!(solve(&start, 2), SolverResult::LimitReached(2)));
+    }
+}
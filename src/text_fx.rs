@@ -1,12 +1,15 @@
 // Rust modules
 use core::f64::consts::PI;
 use std::rc::Rc;
+use std::path::Path;
 
 // External modules
-use sdl2::render::Canvas;
-use sdl2::video::Window;
 use sdl2::rect::Rect;
-use sdl2::render::Texture;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::image::{LoadTexture};
+
+// Local modules
+use crate::render::{Renderer};
 
 const PI_2: f64 = 2.0 * PI;
 
@@ -38,7 +41,21 @@ pub struct Font {
 }
 
 impl Font {
-    fn draw_char(&self, canvas: &mut Canvas<Window>, x: u32, y: u32, c: u8) {
+    // Loads the font atlas, the characters are in ASCII order starting with space
+    pub fn load<T, P: AsRef<Path>>(texture_creator: &TextureCreator<T>, path: P, char_width: u32, char_height: u32) -> Result<Font, String> {
+        let texture = texture_creator.load_texture(path)?;
+        let texture_properties = texture.query();
+
+        Ok(Font {
+            width: char_width,
+            height: char_height,
+            rows: (texture_properties.height / char_height) as u8,
+            cols: (texture_properties.width / char_width) as u8,
+            texture,
+        })
+    }
+
+    fn draw_char(&self, canvas: &mut dyn Renderer, x: u32, y: u32, c: u8) {
         if c < 32 || c > 95 {
             // Outside of character range
             return
@@ -65,6 +82,8 @@ pub struct StaticText {
     height: u32,
     text: String,
     font: Option<Rc<Font>>,
+    // The x position center() was first called with
+    center_x: Option<u32>,
 }
 
 impl StaticText {
@@ -76,16 +95,20 @@ impl StaticText {
             height: 0,
             text: text,
             font: None,
+            center_x: None,
         }
     }
 
+    // Centers the text on its x position. Calling it again, e.g. after a new
+    // font or text, keeps the same center until set_x() is called.
     pub fn center(&mut self) {
         if let Some(_) = &self.font {
-            self.x = self.x - (self.width / 2);
+            let center_x = *self.center_x.get_or_insert(self.x);
+            self.x = center_x - (self.width / 2);
         }
     }
 
-    fn draw_at(&self, x: u32, y: u32, canvas: &mut Canvas<Window>) {
+    fn draw_at(&self, x: u32, y: u32, canvas: &mut dyn Renderer) {
         if let Some(font) = &self.font {
             let mut x2 = x;
 
@@ -96,11 +119,11 @@ impl StaticText {
         }
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
         self.draw_at(self.x, self.y, canvas);
     }
 
-    pub fn draw_offset(&self, dx: i32, dy: i32, canvas: &mut Canvas<Window>) {
+    pub fn draw_offset(&self, dx: i32, dy: i32, canvas: &mut dyn Renderer) {
         let newx = ((self.x as i32) + dx) as u32;
         let newy = ((self.y as i32) + dy) as u32;
         self.draw_at(newx, newy, canvas);
@@ -128,7 +151,16 @@ impl StaticText {
     }
 
     pub fn set_x(&mut self, x: u32) {
-        self.x = x
+        self.x = x;
+        self.center_x = None;
+    }
+
+    // True if the point lies on the text, used for mouse and touch input
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let left = self.x as i32;
+        let top = self.y as i32;
+
+        x >= left && x < left + self.width as i32 && y >= top && y < top + self.height as i32
     }
 
     // TODO: pub fn chars() -> impl Iterator {}
@@ -168,11 +200,12 @@ impl WaveVText {
         }
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+    // Alpha is the time since the last update in updates (0.0..1.0)
+    pub fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
         if let Some(font) = &self.base.font {
             if self.active {
                 let mut x2 = self.base.x;
-                let mut phase = self.phase;
+                let mut phase = self.phase + self.speed * alpha;
 
                 for c in self.base.text.chars() {
                     let y2 = self.base.y + ((self.amplitude * phase.sin()) as u32);
@@ -197,6 +230,10 @@ impl WaveVText {
     pub fn set_font(&mut self, font: &Rc<Font>) {
         self.base.set_font(font);
     }
+
+    pub fn set_x(&mut self, x: u32) {
+        self.base.set_x(x);
+    }
 }
 
 pub struct WaveHText {
@@ -214,10 +251,10 @@ impl WaveHText {
         self.base.update();
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+    pub fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
         if let Some(font) = &self.base.base.font {
             if self.base.active {
-                let mut phase = self.base.phase;
+                let mut phase = self.base.phase + self.base.speed * alpha;
 
                 // base.base. is ugly, maybe use Deref Trait ? 
                 // https://doc.rust-lang.org/std/ops/trait.Deref.html 
@@ -227,7 +264,7 @@ impl WaveHText {
                     phase = phase + self.base.shift;
                 }
             } else {
-                self.base.draw(canvas);
+                self.base.draw(canvas, alpha);
             }
         }
     }
@@ -275,12 +312,14 @@ impl SelectableText {
         self.update_marker_pos();
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+    pub fn draw(&self, canvas: &mut dyn Renderer, alpha: f64) {
         self.base.draw(canvas);
 
         if self.active {
-            self.left_marker.draw_offset(-(self.offset as i32), 0, canvas);
-            self.right_marker.draw_offset(self.offset as i32, 0, canvas);
+            // Interpolated towards the next update, rounded to whole pixels only here
+            let offset = ((self.offset as f64 + alpha).round() as u32 % self.max_offset) as i32;
+            self.left_marker.draw_offset(-offset, 0, canvas);
+            self.right_marker.draw_offset(offset, 0, canvas);
         }
     }
 
@@ -318,4 +357,8 @@ impl SelectableText {
         self.base.set_x(x);
         self.update_marker_pos();
     }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.base.contains(x, y)
+    }
 }
@@ -0,0 +1,77 @@
+// Rust modules
+use std::path::Path;
+
+// External modules
+
+// Local modules
+use crate::level_pack::{LevelPack};
+use crate::solver::{self, SolverResult, MAX_STATES};
+use crate::board::{Direction};
+
+// Levels that can be solved with less moves are reported as trivial
+const TRIVIAL_MOVES: usize = 3;
+
+// Solves every level in the pack and prints a report.
+// Returns false if any level could not be loaded, is unsolvable, is trivial or
+// has a par that is lower than the minimal number of moves.
+pub fn verify_pack<T: AsRef<Path>>(path: T) -> bool {
+    let pack = match LevelPack::load(path) {
+        Ok(pack) => pack,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return false
+        }
+    };
+
+    println!("Level pack: '{}' by {}, {} levels", pack.name, pack.author, pack.len());
+
+    let mut valid = true;
+
+    for index in 0..pack.len() {
+        let info = &pack.levels[index];
+
+        let level = match pack.load_level(index) {
+            Ok(level) => level,
+            Err(e) => {
+                println!("{:3} {}: ERROR: {}", index + 1, info.file, e);
+                valid = false;
+                continue
+            }
+        };
+
+        match solver::solve(&level.board, MAX_STATES) {
+            SolverResult::Solved(solution) => {
+                let moves = solution.len();
+
+                println!("{:3} {} ('{}'): {} moves, par {}, solution: {}",
+                    index + 1, info.file, level.name, moves, info.par_moves, solution_to_string(&solution));
+
+                if (info.par_moves as usize) < moves {
+                    println!("    ERROR: par is lower than the minimal number of moves");
+                    valid = false;
+                } else if (info.par_moves as usize) > moves {
+                    println!("    WARNING: par is higher than the minimal number of moves");
+                }
+
+                if moves < TRIVIAL_MOVES {
+                    println!("    ERROR: level is trivial");
+                    valid = false;
+                }
+            },
+            SolverResult::Unsolvable(states) => {
+                println!("{:3} {} ('{}'): ERROR: unsolvable ({} states checked)", index + 1, info.file, level.name, states);
+                valid = false;
+            },
+            SolverResult::LimitReached(states) => {
+                println!("{:3} {} ('{}'): ERROR: no solution found within {} states", index + 1, info.file, level.name, states);
+                valid = false;
+            }
+        }
+    }
+
+    valid
+}
+
+fn solution_to_string(solution: &[Direction]) -> String {
+    solution.iter().map(|d| d.to_char()).collect()
+}
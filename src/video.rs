@@ -0,0 +1,149 @@
+// Rust modules
+
+// External modules
+use sdl2::render::Canvas;
+use sdl2::video::{Window, FullscreenType, WindowPos, DisplayMode};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::sys;
+
+// Local modules
+use crate::settings::{GameSettings, ScreenMode};
+
+// All screens are laid out for this size, SDL scales it to the real window size
+pub const LOGICAL_WIDTH: u32 = 800;
+pub const LOGICAL_HEIGHT: u32 = 600;
+
+// Used if SDL can't tell which modes the display supports
+const FALLBACK_MODES: [(u32, u32); 4] = [(800, 600), (1024, 768), (1280, 1024), (1920, 1080)];
+
+// Changes the window to match the video settings
+pub fn apply_video_settings(canvas: &mut Canvas<Window>, settings: &GameSettings) -> Result<(), String> {
+    let screen_mode = settings.get_screen_mode();
+
+    {
+        let window = canvas.window_mut();
+
+        // Leave fullscreen first, otherwise the new size is ignored
+        window.set_fullscreen(FullscreenType::Off)?;
+        window.set_size(screen_mode.width, screen_mode.height).map_err(|e| e.to_string())?;
+
+        if settings.get_fullscreen() {
+            if settings.get_exclusive_fullscreen() {
+                // An unknown format keeps the desktop format,
+                // SDL picks the closest mode the display supports
+                let mode = DisplayMode::new(PixelFormatEnum::Unknown,
+                    screen_mode.width as i32, screen_mode.height as i32, screen_mode.refresh_rate);
+                window.set_display_mode(Some(mode))?;
+                window.set_fullscreen(FullscreenType::True)?;
+            } else {
+                window.set_fullscreen(FullscreenType::Desktop)?;
+            }
+        } else {
+            window.set_position(WindowPos::Centered, WindowPos::Centered);
+        }
+    }
+
+    apply_scaling(canvas, settings)
+}
+
+// Smooth scaling filters the textures, integer scaling keeps the pixels sharp.
+// SDL only uses the hint for textures that are created afterwards.
+pub fn set_scale_quality(settings: &GameSettings) {
+    let quality = if settings.get_integer_scaling() { "nearest" } else { "linear" };
+    sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", quality);
+}
+
+// Maps the logical canvas onto the window. The aspect ratio is kept,
+// unused parts of the window stay black (letterbox).
+pub fn apply_scaling(canvas: &mut Canvas<Window>, settings: &GameSettings) -> Result<(), String> {
+    canvas.set_logical_size(LOGICAL_WIDTH, LOGICAL_HEIGHT).map_err(|e| e.to_string())?;
+
+    let integer_scale = if settings.get_integer_scaling() {
+        sys::SDL_bool::SDL_TRUE
+    } else {
+        sys::SDL_bool::SDL_FALSE
+    };
+
+    // Not wrapped by the sdl2 crate yet
+    let result = unsafe { sys::SDL_RenderSetIntegerScale(canvas.raw(), integer_scale) };
+
+    if result != 0 {
+        return Err(sdl2::get_error())
+    }
+
+    Ok(())
+}
+
+// All modes of the display the window is on, smallest first.
+// Modes smaller than the logical canvas are left out.
+pub fn query_screen_modes(window: &Window) -> Vec<ScreenMode> {
+    let mut modes = match read_display_modes(window) {
+        Ok(modes) => modes,
+        Err(e) => {
+            println!("Could not query display modes ({}), using default", e);
+            Vec::new()
+        }
+    };
+
+    modes.retain(|mode| mode.width >= LOGICAL_WIDTH && mode.height >= LOGICAL_HEIGHT);
+
+    if modes.is_empty() {
+        modes = FALLBACK_MODES.iter()
+            .map(|&(width, height)| ScreenMode::new(width, height, 0))
+            .collect();
+    }
+
+    modes.sort_by_key(|mode| (mode.width, mode.height, mode.refresh_rate));
+    modes.dedup();
+    modes
+}
+
+// The mode from the list that matches the wanted one best:
+// the same mode, the same size with another refresh rate,
+// or the biggest mode that is not bigger than the wanted one.
+pub fn closest_screen_mode(modes: &[ScreenMode], wanted: ScreenMode) -> ScreenMode {
+    if modes.contains(&wanted) {
+        return wanted
+    }
+
+    let same_size = modes.iter()
+        .filter(|mode| mode.width == wanted.width && mode.height == wanted.height)
+        .max_by_key(|mode| mode.refresh_rate);
+
+    if let Some(mode) = same_size {
+        return *mode
+    }
+
+    modes.iter()
+        .rfind(|mode| mode.width <= wanted.width && mode.height <= wanted.height)
+        .or_else(|| modes.first())
+        .cloned()
+        .unwrap_or(wanted)
+}
+
+// Converts a position relative to the window (0.0 ..= 1.0) like the one of a touch
+// into logical coordinates, the same way SDL does it for mouse events
+pub fn window_to_logical(canvas: &Canvas<Window>, x: f32, y: f32) -> (i32, i32) {
+    let (window_width, window_height) = canvas.window().size();
+    let (scale_x, scale_y) = canvas.scale();
+    let viewport = canvas.viewport();
+
+    let logical_x = x * window_width as f32 / scale_x - viewport.x() as f32;
+    let logical_y = y * window_height as f32 / scale_y - viewport.y() as f32;
+
+    (logical_x as i32, logical_y as i32)
+}
+
+fn read_display_modes(window: &Window) -> Result<Vec<ScreenMode>, String> {
+    let video = window.subsystem();
+    let display = window.display_index()?;
+    let count = video.num_display_modes(display)?;
+    let mut modes = Vec::new();
+
+    for index in 0..count {
+        let mode = video.display_mode(display, index)?;
+        modes.push(ScreenMode::new(mode.w as u32, mode.h as u32, mode.refresh_rate));
+    }
+
+    Ok(modes)
+}